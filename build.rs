@@ -0,0 +1,172 @@
+//! Picks the puzzle text each day embeds and writes `inputs.rs` into
+//! `OUT_DIR` with one `DAYN` constant per day found.
+//!
+//! Inputs are read from `data/dayN/` unless `AOC_DATA_DIR` names another
+//! directory with the same layout, such as a git-ignored `aoc-data/` holding
+//! your own account's inputs. Relative paths are taken from the crate root,
+//! and any file missing there is taken from `data/` instead.
+//! The `example` feature embeds `example.txt` in place of `input.txt`.
+//!
+//! With the `preparsed` feature the same inputs are also parsed here into
+//! `preparsed.rs`, a module per day of `static` tables the solvers read
+//! straight from flash.
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+// The same shape checks the runtime grid parsers make
+#[allow(dead_code)]
+#[path = "src/util/grid.rs"]
+mod grid;
+
+const DAYS: u32 = 25;
+
+// Must match day5's `MATRIX_PAGES`
+const MATRIX_PAGES: usize = 100;
+
+fn enabled(day: u32) -> bool {
+    env::var_os(format!("CARGO_FEATURE_DAY{}", day)).is_some()
+}
+
+fn main() {
+    let root = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let data_dir = match env::var_os("AOC_DATA_DIR") {
+        Some(dir) => root.join(dir),
+        None => root.join("data")
+    };
+    let file_name = match env::var_os("CARGO_FEATURE_EXAMPLE") {
+        Some(_) => "example.txt",
+        None => "input.txt"
+    };
+    println!("cargo:rerun-if-env-changed=AOC_DATA_DIR");
+    // Both directories, since any file missing from one is taken from the other
+    for dir in [&data_dir, &root.join("data")] {
+        if dir.is_dir() {
+            println!("cargo:rerun-if-changed={}", dir.display());
+        }
+    }
+
+    let mut inputs = String::new();
+    let mut texts = Vec::new();
+    for day in 1..=DAYS {
+        let day_dir = format!("day{}", day);
+        let path = data_dir.join(&day_dir).join(file_name);
+        let fallback = root.join("data").join(&day_dir).join(file_name);
+
+        let found = if path.is_file() {
+            Some(path.clone())
+        } else if fallback.is_file() {
+            println!("cargo:warning={} does not exist, using {}", path.display(), fallback.display());
+            Some(fallback)
+        } else {
+            None
+        };
+        match found {
+            Some(path) => {
+                let path = fs::canonicalize(&path).unwrap();
+                writeln!(inputs, "pub const DAY{}: &str = include_str!({:?});", day, path).unwrap();
+                if enabled(day) {
+                    texts.push((day, fs::read_to_string(&path).unwrap()));
+                }
+            }
+            None if enabled(day) && root.join(format!("src/day{}.rs", day)).is_file() => {
+                println!("cargo:warning=day{} is enabled but {} does not exist", day, path.display());
+            }
+            None => {}
+        }
+    }
+
+    let mut preparsed = String::new();
+    if env::var_os("CARGO_FEATURE_PREPARSED").is_some() {
+        for (day, text) in &texts {
+            let tables = match day {
+                1 => day1(text),
+                4 | 6 => grid(text),
+                5 => day5(text),
+                7 => day7(text),
+                _ => continue
+            };
+            writeln!(preparsed, "pub mod day{} {{\n{}}}\n", day, tables).unwrap();
+        }
+    }
+
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    fs::write(out.join("inputs.rs"), inputs).unwrap();
+    fs::write(out.join("preparsed.rs"), preparsed).unwrap();
+}
+
+fn rows(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().map(str::trim_end).filter(|l| !l.is_empty())
+}
+
+fn table<T: std::fmt::Debug>(name: &str, ty: &str, values: &[T]) -> String {
+    format!("    pub static {}: [{}; {}] = {:?};\n", name, ty, values.len(), values)
+}
+
+// Both location lists, already sorted
+fn day1(text: &str) -> String {
+    let (mut list_a, mut list_b) = (Vec::new(), Vec::new());
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        let mut iter = line.split_whitespace().map(|x| x.parse::<u32>().unwrap());
+        list_a.push(iter.next().unwrap());
+        list_b.push(iter.next().unwrap());
+    }
+    list_a.sort_unstable();
+    list_b.sort_unstable();
+    table("LIST_A", "u32", &list_a) + &table("LIST_B", "u32", &list_b)
+}
+
+// The rows packed one after another without their newlines. Fails the build
+// on a grid the runtime parsers would reject.
+fn grid(text: &str) -> String {
+    let (width, height, _) = grid::grid_shape(text)
+        .unwrap_or_else(|e| panic!("line {} of a grid input does not line up with the first", e.line));
+    let cells: String = rows(text).collect();
+    format!(
+        "    pub const WIDTH: usize = {};\n    pub const HEIGHT: usize = {};\n    pub static GRID: [u8; {}] = *b{:?};\n",
+        width, height, cells.len(), cells
+    )
+}
+
+// Rules as day5's bit matrix, with any rule naming a larger page kept apart.
+// Updates are runs of PAGES given as (start, len).
+fn day5(text: &str) -> String {
+    let mut matrix = vec![0u32; (MATRIX_PAGES * MATRIX_PAGES).div_ceil(32)];
+    let mut fallback = Vec::new();
+    let mut pages = Vec::new();
+    let mut updates = Vec::new();
+    for line in rows(text) {
+        if let Some((a, b)) = line.split_once('|') {
+            let (a, b): (u8, u8) = (a.parse().unwrap(), b.parse().unwrap());
+            if (a as usize) < MATRIX_PAGES && (b as usize) < MATRIX_PAGES {
+                let i = a as usize * MATRIX_PAGES + b as usize;
+                matrix[i / 32] |= 1 << (i % 32);
+            } else {
+                fallback.push((a, b));
+            }
+        } else {
+            let start = pages.len();
+            pages.extend(line.split(',').map(|x| x.parse::<u8>().unwrap()));
+            updates.push((start, pages.len() - start));
+        }
+    }
+    table("RULES", "u32", &matrix)
+        + &table("FALLBACK", "(u8, u8)", &fallback)
+        + &table("PAGES", "u8", &pages)
+        + &table("UPDATES", "(usize, usize)", &updates)
+}
+
+// Equations as (test value, start, len) over the runs of OPERANDS
+fn day7(text: &str) -> String {
+    let mut operands = Vec::new();
+    let mut equations = Vec::new();
+    for line in rows(text) {
+        let (result, rest) = line.split_once(": ").unwrap();
+        let start = operands.len();
+        operands.extend(rest.split(' ').map(|x| x.parse::<u64>().unwrap()));
+        equations.push((result.parse::<u64>().unwrap(), start, operands.len() - start));
+    }
+    table("OPERANDS", "u64", &operands) + &table("EQUATIONS", "(u64, usize, usize)", &equations)
+}
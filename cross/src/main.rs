@@ -12,17 +12,9 @@ use embassy_executor::Spawner;
 
 #[embassy_executor::main]
 async fn main(_spawner: Spawner) {
-    info!("day 1a answer = {}", day1a::answer());
-    info!("day 1b answer = {}", day1b::answer());
-    info!("day 2a answer = {}", day2a::answer());
-    info!("day 2b answer = {}", day2b::answer());
-    info!("day 3a answer = {}", day3a::answer());
-    info!("day 3b answer = {}", day3b::answer());
-    info!("day 4a answer = {}", day4a::answer());
-    info!("day 4b answer = {}", day4b::answer());
-    info!("day 5a answer = {}", day5a::answer());
-    info!("day 5b answer = {}", day5b::answer());
-    info!("day 6a answer = {}", day6a::answer());
-    info!("day 7a answer = {}", day7a::answer());
-    info!("day 7b answer = {}", day7b::answer());
+    for solver in registry::SOLVERS {
+        let (a, b) = solver.run();
+        info!("day {}a answer = {}", solver.day, a);
+        info!("day {}b answer = {}", solver.day, b);
+    }
 }
@@ -0,0 +1,143 @@
+use core::fmt;
+use heapless::String;
+
+// Long enough for the comma separated text answers some puzzles ask for
+pub const TEXT_MAX_LEN: usize = 48;
+
+// A puzzle answer of any of the types the days produce. Numbers compare
+// by value whichever variant holds them, so `Answer::from(5u16) == 5u64`.
+#[derive(Clone, Debug)]
+pub enum Answer {
+    Unsigned(u64),
+    Signed(i64),
+    Wide(u128),
+    Text(String<TEXT_MAX_LEN>),
+}
+
+// Numbers brought to one form so every variant can be compared
+#[derive(PartialEq)]
+enum Key<'a> {
+    Negative(i64),
+    Number(u128),
+    Text(&'a str),
+}
+
+impl Answer {
+    // Truncates text longer than TEXT_MAX_LEN at a char boundary
+    pub fn text(text: &str) -> Self {
+        let mut s = String::new();
+        for c in text.chars() {
+            if s.push(c).is_err() {
+                break;
+            }
+        }
+        Answer::Text(s)
+    }
+
+    fn key(&self) -> Key<'_> {
+        match self {
+            Answer::Unsigned(n) => Key::Number(*n as u128),
+            Answer::Signed(n) if *n < 0 => Key::Negative(*n),
+            Answer::Signed(n) => Key::Number(*n as u128),
+            Answer::Wide(n) => Key::Number(*n),
+            Answer::Text(s) => Key::Text(s)
+        }
+    }
+}
+
+impl PartialEq for Answer {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Answer {}
+
+macro_rules! impl_from {
+    ($variant:ident, $wide:ty, $($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::$variant(n as $wide)
+                }
+            }
+
+            impl PartialEq<$t> for Answer {
+                fn eq(&self, other: &$t) -> bool {
+                    self.key() == Answer::$variant(*other as $wide).key()
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(Unsigned, u64, u8, u16, u32, u64, usize);
+impl_from!(Signed, i64, i8, i16, i32, i64, isize);
+impl_from!(Wide, u128, u128);
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Answer::text(text)
+    }
+}
+
+impl PartialEq<&str> for Answer {
+    fn eq(&self, other: &&str) -> bool {
+        self.key() == Key::Text(other)
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Unsigned(n) => write!(f, "{}", n),
+            Answer::Signed(n) => write!(f, "{}", n),
+            Answer::Wide(n) => write!(f, "{}", n),
+            Answer::Text(s) => f.write_str(s)
+        }
+    }
+}
+
+impl defmt::Format for Answer {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Answer::Unsigned(n) => defmt::write!(f, "{=u64}", n),
+            Answer::Signed(n) => defmt::write!(f, "{=i64}", n),
+            Answer::Wide(n) => defmt::write!(f, "{=u128}", n),
+            Answer::Text(s) => defmt::write!(f, "{=str}", s.as_str())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equality() {
+        assert_eq!(Answer::from(4602usize), 4602u64);
+        assert_eq!(Answer::from(334u16), Answer::Wide(334));
+        assert_eq!(Answer::from(7i32), 7u8);
+        assert_ne!(Answer::from(-1i64), Answer::Wide(u64::MAX as u128 + 1));
+        assert_ne!(Answer::from(-1i64), u64::MAX);
+        assert_eq!(Answer::from("ab,cd"), "ab,cd");
+        assert_ne!(Answer::from("12"), 12u32);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(std::format!("{}", Answer::from(11387u64)), "11387");
+        assert_eq!(std::format!("{}", Answer::from(-42i32)), "-42");
+        assert_eq!(std::format!("{}", Answer::from(u128::MAX)), "340282366920938463463374607431768211455");
+        assert_eq!(std::format!("{}", Answer::from("4,6,3,5")), "4,6,3,5");
+    }
+
+    #[test]
+    fn test_text_truncated() {
+        let long = "x".repeat(TEXT_MAX_LEN + 5);
+        let Answer::Text(s) = Answer::text(&long) else {
+            panic!("expected text");
+        };
+        assert_eq!(s.len(), TEXT_MAX_LEN);
+    }
+}
@@ -0,0 +1,313 @@
+use heapless::Vec;
+use crate::util::grid::count_lines;
+use crate::util::scan::{self, digits, skip_whitespace, ScanError};
+
+pub const INPUT_CONTENT: &str = crate::inputs::DAY1;
+const INPUT_NUM_LINES: usize = count_lines(INPUT_CONTENT);
+
+pub type Id = u32;
+pub type IdList = Vec<Id, INPUT_NUM_LINES>;
+
+// Location IDs in the puzzle input have five digits
+pub const ID_BITS: u32 = 17;
+const RADIX_BITS: u32 = 9;
+const RADIX: usize = 1 << RADIX_BITS;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ParseErrorKind {
+    InvalidId,
+    IdOutOfRange,
+    MissingId,
+    TooManyIds,
+    TooManyLines,
+}
+
+// `line` counts from 1
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+// IDs must be whole words, so "12abc" is not read as 12
+fn parse_id(word: &str) -> Result<Id, ParseErrorKind> {
+    match scan::integer(word.as_bytes()) {
+        Ok((id, [])) => Ok(id),
+        Err(ScanError::Overflow) => Err(ParseErrorKind::IdOutOfRange),
+        _ => Err(ParseErrorKind::InvalidId)
+    }
+}
+
+fn parse_line(line: &str) -> Result<(Id, Id), ParseErrorKind> {
+    let mut words = line.split_ascii_whitespace();
+    let a = parse_id(words.next().ok_or(ParseErrorKind::MissingId)?)?;
+    let b = parse_id(words.next().ok_or(ParseErrorKind::MissingId)?)?;
+    if words.next().is_some() {
+        return Err(ParseErrorKind::TooManyIds);
+    }
+    Ok((a, b))
+}
+
+pub fn parse_lists<const N: usize>(text: &str) -> Result<(Vec<Id, N>, Vec<Id, N>), ParseError> {
+    let mut list_a = Vec::new();
+    let mut list_b = Vec::new();
+    for (i, line) in text.trim_end().lines().enumerate() {
+        let error = |kind| ParseError { line: i + 1, kind };
+        let (a, b) = parse_line(line).map_err(error)?;
+        list_a.push(a).map_err(|_| error(ParseErrorKind::TooManyLines))?;
+        list_b.push(b).map_err(|_| error(ParseErrorKind::TooManyLines))?;
+    }
+
+    Ok((list_a, list_b))
+}
+
+// LSD radix sort in two counting passes of 9 bits, enough for any ID below
+// 2^18. Runs in O(n) with one scratch list and no comparisons. Lists with a
+// wider ID fall back to a comparison sort.
+pub fn radix_sort<const N: usize>(list: &mut Vec<Id, N>) {
+    if list.iter().any(|&id| id >> (2 * RADIX_BITS) != 0) {
+        list.sort_unstable();
+        return;
+    }
+    let mut scratch: Vec<Id, N> = Vec::new();
+    let _ = scratch.resize(list.len(), 0);
+
+    counting_pass(list, &mut scratch, 0);
+    counting_pass(&scratch, list, RADIX_BITS);
+}
+
+// Stable sort of `from` into `to` on the digit at `shift`
+fn counting_pass(from: &[Id], to: &mut [Id], shift: u32) {
+    let digit = |id: Id| (id >> shift) as usize & (RADIX - 1);
+
+    let mut starts = [0usize; RADIX];
+    for &id in from {
+        starts[digit(id)] += 1;
+    }
+    let mut total = 0;
+    for start in starts.iter_mut() {
+        let count = *start;
+        *start = total;
+        total += count;
+    }
+    for &id in from {
+        let d = digit(id);
+        to[starts[d]] = id;
+        starts[d] += 1;
+    }
+}
+
+pub fn sort<const N: usize>(list: &mut Vec<Id, N>) {
+    #[cfg(feature = "counting-sort")]
+    radix_sort(list);
+    #[cfg(not(feature = "counting-sort"))]
+    list.sort_unstable();
+}
+
+// Heapsort, which needs no scratch space and runs in a const context
+pub const fn const_sort(list: &mut [Id]) {
+    let n = list.len();
+    let mut i = n / 2;
+    while i > 0 {
+        i -= 1;
+        sift_down(list, i, n);
+    }
+    let mut end = n;
+    while end > 1 {
+        end -= 1;
+        list.swap(0, end);
+        sift_down(list, 0, end);
+    }
+}
+
+const fn sift_down(list: &mut [Id], mut root: usize, end: usize) {
+    loop {
+        let mut child = 2 * root + 1;
+        if child >= end {
+            break;
+        }
+        if child + 1 < end && list[child + 1] > list[child] {
+            child += 1;
+        }
+        if list[root] >= list[child] {
+            break;
+        }
+        list.swap(root, child);
+        root = child;
+    }
+}
+
+// Both lists must be sorted
+pub const fn distance(list_a: &[Id], list_b: &[Id]) -> u32 {
+    let mut total = 0;
+    let mut i = 0;
+    while i < list_a.len() && i < list_b.len() {
+        total += list_a[i].abs_diff(list_b[i]);
+        i += 1;
+    }
+    total
+}
+
+const fn run_len(list: &[Id], from: usize, id: Id) -> usize {
+    let mut i = from;
+    while i < list.len() && list[i] == id {
+        i += 1;
+    }
+    i - from
+}
+
+// Walks the two sorted lists together, so each run of equal IDs in `list_a`
+// is matched against the run of the same ID in `list_b` once
+pub const fn similarity(list_a: &[Id], list_b: &[Id]) -> usize {
+    let (mut i, mut j) = (0, 0);
+    let mut total = 0;
+    while i < list_a.len() && j < list_b.len() {
+        let id = list_a[i];
+        if list_b[j] < id {
+            j += 1;
+            continue;
+        }
+
+        let run_a = run_len(list_a, i, id);
+        let run_b = run_len(list_b, j, id);
+        total += run_a * run_b * id as usize;
+        i += run_a;
+        j += run_b;
+    }
+    total
+}
+
+// Both parts from one parse and one sort of each list
+pub fn solve_both(input: &str) -> (u32, usize) {
+    let (mut list_a, mut list_b): (IdList, IdList) = parse_lists(input).unwrap();
+    sort(&mut list_a);
+    sort(&mut list_b);
+    (distance(&list_a, &list_b), similarity(&list_a, &list_b))
+}
+
+// Lists shorter than N are padded with zeros, which sort to the front of
+// both lists and add nothing to either answer
+pub const fn const_parse_lists<const N: usize>(text: &str) -> ([Id; N], [Id; N]) {
+    let bytes = text.as_bytes();
+    let (mut list_a, mut list_b) = ([0; N], [0; N]);
+    let mut i = 0;
+    let mut line = 0;
+    while let Some((a, next)) = digits(bytes, skip_whitespace(bytes, i)) {
+        let Some((b, next)) = digits(bytes, skip_whitespace(bytes, next)) else {
+            panic!("line is missing its second ID");
+        };
+        assert!(line < N, "too many lines");
+        assert!(a <= Id::MAX as u64 && b <= Id::MAX as u64, "ID out of range");
+        list_a[line] = a as Id;
+        list_b[line] = b as Id;
+        line += 1;
+        i = next;
+    }
+    // Anything but whitespace left means a line that is not two IDs
+    assert!(skip_whitespace(bytes, i) == bytes.len(), "invalid ID");
+
+    (list_a, list_b)
+}
+
+pub const fn const_solve(input: &str) -> (u32, usize) {
+    let (mut list_a, mut list_b) = const_parse_lists::<INPUT_NUM_LINES>(input);
+    const_sort(&mut list_a);
+    const_sort(&mut list_b);
+    (distance(&list_a, &list_b), similarity(&list_a, &list_b))
+}
+
+pub const ANSWERS: (u32, usize) = const_solve(INPUT_CONTENT);
+
+#[cfg(feature = "preparsed")]
+pub fn solve_preparsed() -> (u32, usize) {
+    use crate::preparsed::day1::{LIST_A, LIST_B};
+    (distance(&LIST_A, &LIST_B), similarity(&LIST_A, &LIST_B))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::line_ending_variants;
+
+    const EXAMPLE_CONTENT: &str = include_str!("../data/day1/example.txt");
+
+    fn sorted_example() -> (Vec<Id, 6>, Vec<Id, 6>) {
+        let (mut list_a, mut list_b) = parse_lists(EXAMPLE_CONTENT).unwrap();
+        sort(&mut list_a);
+        sort(&mut list_b);
+        (list_a, list_b)
+    }
+
+    #[test]
+    fn test_example() {
+        let (list_a, list_b) = sorted_example();
+        assert_eq!(distance(&list_a, &list_b), 11);
+        assert_eq!(similarity(&list_a, &list_b), 31);
+        assert_eq!(solve_both(EXAMPLE_CONTENT), (11, 31));
+    }
+
+    #[test]
+    fn test_line_endings() {
+        for text in line_ending_variants(EXAMPLE_CONTENT) {
+            assert_eq!(const_solve(&text), (11, 31), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |line, kind| Some(ParseError { line, kind });
+        assert_eq!(parse_lists::<4>("1   2\n3   -4\n").err(), error(2, ParseErrorKind::InvalidId));
+        assert_eq!(parse_lists::<4>("12a   34\n").err(), error(1, ParseErrorKind::InvalidId));
+        assert_eq!(parse_lists::<4>("12abc   x34\n").err(), error(1, ParseErrorKind::InvalidId));
+        assert_eq!(parse_lists::<4>("1   2\n3\n").err(), error(2, ParseErrorKind::MissingId));
+        assert_eq!(parse_lists::<4>("1   2   3\n").err(), error(1, ParseErrorKind::TooManyIds));
+        assert_eq!(parse_lists::<4>("1   99999999999\n").err(), error(1, ParseErrorKind::IdOutOfRange));
+        assert_eq!(parse_lists::<1>("1   2\n3   4\n").err(), error(2, ParseErrorKind::TooManyLines));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid ID")]
+    fn test_const_parse_error() {
+        const_parse_lists::<4>("1 2\nx 3\n5 6\n");
+    }
+
+    #[test]
+    fn test_radix_sort() {
+        let (mut list_a, mut list_b): (Vec<Id, 1000>, Vec<Id, 1000>) = parse_lists(INPUT_CONTENT).unwrap();
+        let (mut expected_a, mut expected_b) = (list_a.clone(), list_b.clone());
+        expected_a.sort_unstable();
+        expected_b.sort_unstable();
+        radix_sort(&mut list_a);
+        radix_sort(&mut list_b);
+        assert_eq!(list_a, expected_a);
+        assert_eq!(list_b, expected_b);
+
+        let mut edge: Vec<Id, 5> = Vec::from_slice(&[(1 << ID_BITS) - 1, 0, 512, 511, 0]).unwrap();
+        radix_sort(&mut edge);
+        assert_eq!(edge, [0, 0, 511, 512, (1 << ID_BITS) - 1]);
+
+        let mut wide: Vec<Id, 4> = Vec::from_slice(&[1 << 18, 3, Id::MAX, 1]).unwrap();
+        radix_sort(&mut wide);
+        assert_eq!(wide, [1, 3, 1 << 18, Id::MAX]);
+    }
+
+    #[test]
+    fn test_const_solve() {
+        assert_eq!(const_solve(EXAMPLE_CONTENT), (11, 31));
+
+        let mut list = [5, 1, 4, 1, 3, 0, 2];
+        const_sort(&mut list);
+        assert_eq!(list, [0, 1, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_similarity_matches_quadratic() {
+        let (mut list_a, mut list_b): (Vec<Id, 1000>, Vec<Id, 1000>) = parse_lists(INPUT_CONTENT).unwrap();
+        let expected: usize = list_a.iter()
+            .map(|&a| list_b.iter().filter(|&&b| b == a).count() * a as usize)
+            .sum();
+        sort(&mut list_a);
+        sort(&mut list_b);
+        assert_eq!(similarity(&list_a, &list_b), expected);
+    }
+}
@@ -0,0 +1,441 @@
+use heapless::Vec;
+use crate::util::grid::count_lines;
+use crate::util::scan::{self, digits, skip_blanks, ScanError};
+
+pub const INPUT_CONTENT: &str = crate::inputs::DAY2;
+const INPUT_NUM_LINES: usize = count_lines(INPUT_CONTENT);
+
+pub const MAX_LEVELS_PER_REPORT: usize = 8;
+
+pub type Level = i16;
+pub type Report = Vec<Level, MAX_LEVELS_PER_REPORT>;
+pub type Removed = Vec<usize, MAX_LEVELS_PER_REPORT>;
+pub type AllReports = Vec<Report, INPUT_NUM_LINES>;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ParseErrorKind {
+    InvalidLevel,
+    LevelOutOfRange,
+    TooManyLevels,
+    TooManyReports,
+}
+
+// `line` counts from 1
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+// Spaces between levels, and the \r of a CRLF line ending
+const BLANKS: &[u8] = b" \t\r";
+
+pub fn parse_report(line: &str) -> Result<Report, ParseErrorKind> {
+    let mut report = Report::new();
+    let mut rest = scan::skip_separators(line.as_bytes(), BLANKS);
+    while !rest.is_empty() {
+        let (level, after) = scan::integer(rest).map_err(|e| match e {
+            ScanError::Overflow | ScanError::Negative => ParseErrorKind::LevelOutOfRange,
+            ScanError::NoDigits => ParseErrorKind::InvalidLevel
+        })?;
+        // Levels must be whole words, so "1x" is not read as 1
+        if after.first().is_some_and(|b| !b.is_ascii_whitespace()) {
+            return Err(ParseErrorKind::InvalidLevel);
+        }
+        report.push(level).map_err(|_| ParseErrorKind::TooManyLevels)?;
+        rest = scan::skip_separators(after, BLANKS);
+    }
+    Ok(report)
+}
+
+pub fn parse<const N: usize>(text: &str) -> Result<Vec<Report, N>, ParseError> {
+    let mut reports = Vec::new();
+    for (i, line) in text.trim_end().lines().enumerate() {
+        let report = parse_report(line).map_err(|kind| ParseError { line: i + 1, kind })?;
+        reports.push(report)
+            .map_err(|_| ParseError { line: i + 1, kind: ParseErrorKind::TooManyReports })?;
+    }
+    Ok(reports)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Trend {
+    Increasing,
+    Decreasing,
+}
+
+impl Trend {
+    fn sign(self) -> i32 {
+        match self {
+            Trend::Increasing => 1,
+            Trend::Decreasing => -1
+        }
+    }
+}
+
+// Why the step onto a level broke the report
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Fault {
+    Zero,
+    TooSmall,
+    TooLarge,
+    DirectionChange,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict {
+    Safe(Trend),
+    // The fewest levels whose removal makes the report safe
+    SafeAfterRemoving { trend: Trend, removed: Removed },
+    // The first step that breaks the report, onto the level at `position`.
+    // The trend is taken from the first step.
+    Unsafe { position: usize, fault: Fault },
+}
+
+// A report is safe when its levels all step the same way by between
+// `min_step` and `max_step`, once up to `max_removals` levels are dropped
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SafetyPolicy {
+    pub min_step: u8,
+    pub max_step: u8,
+    pub max_removals: usize,
+}
+
+impl SafetyPolicy {
+    pub const STRICT: SafetyPolicy = SafetyPolicy { min_step: 1, max_step: 3, max_removals: 0 };
+    pub const DAMPENED: SafetyPolicy = SafetyPolicy { min_step: 1, max_step: 3, max_removals: 1 };
+
+    const fn step_ok(&self, a: Level, b: Level, sign: i32) -> bool {
+        let step = (b as i32 - a as i32) * sign;
+        step >= self.min_step as i32 && step <= self.max_step as i32
+    }
+
+    pub const fn is_safe(&self, report: &[Level]) -> bool {
+        self.is_safe_in(report, 1) || self.is_safe_in(report, -1)
+    }
+
+    pub fn classify(&self, report: &Report) -> Verdict {
+        let first_fault = self.first_fault(report);
+        let trend = match report.windows(2).next() {
+            Some(w) if w[1] < w[0] => Trend::Decreasing,
+            _ => Trend::Increasing
+        };
+        if first_fault.is_none() {
+            return Verdict::Safe(trend);
+        }
+
+        let best = [Trend::Increasing, Trend::Decreasing].into_iter()
+            .filter_map(|trend| Some((trend, self.removals(report, trend)?)))
+            .min_by_key(|(_, removed)| removed.len());
+        match (best, first_fault) {
+            (Some((trend, removed)), _) => Verdict::SafeAfterRemoving { trend, removed },
+            (None, Some((position, fault))) => Verdict::Unsafe { position, fault },
+            (None, None) => unreachable!()
+        }
+    }
+
+    fn first_fault(&self, report: &[Level]) -> Option<(usize, Fault)> {
+        let sign = match report.windows(2).next() {
+            Some(w) if w[1] < w[0] => -1,
+            _ => 1
+        };
+        report.windows(2).enumerate().find_map(|(i, w)| {
+            if self.step_ok(w[0], w[1], sign) {
+                return None;
+            }
+            // A zero step only gets here when min_step rules it out
+            let step = (w[1] as i32 - w[0] as i32) * sign;
+            let fault = if step == 0 {
+                Fault::Zero
+            } else if step < 0 {
+                Fault::DirectionChange
+            } else if step < self.min_step as i32 {
+                Fault::TooSmall
+            } else {
+                Fault::TooLarge
+            };
+            Some((i + 1, fault))
+        })
+    }
+
+    // Same programme as `is_safe_in`, keeping every mask so the fewest
+    // removals can be traced back
+    fn removals(&self, report: &Report, trend: Trend) -> Option<Removed> {
+        let sign = trend.sign();
+        let k = self.max_removals.min(report.len());
+        let n = report.len();
+        if n == 0 {
+            return Some(Removed::new());
+        }
+
+        let mut reachable = [0u32; MAX_LEVELS_PER_REPORT];
+        for i in 0..n {
+            let mut bits = if i <= k { 1 << i } else { 0 };
+            for gap in 0..=k {
+                let Some(j) = i.checked_sub(gap + 1) else {
+                    break;
+                };
+                if self.step_ok(report[j], report[i], sign) {
+                    bits |= reachable[j] << gap;
+                }
+            }
+            reachable[i] = bits & allowed(k);
+        }
+
+        // Pick the last kept level and removal count with the fewest in total
+        let (mut i, mut r) = (0..n)
+            .filter(|&i| n - 1 - i <= k)
+            .flat_map(|i| (0..=k - (n - 1 - i)).map(move |r| (i, r)))
+            .filter(|&(i, r)| reachable[i] & (1 << r) != 0)
+            .min_by_key(|&(i, r)| r + n - 1 - i)?;
+
+        let mut removed: Removed = ((i + 1)..n).collect();
+        loop {
+            if r == i {
+                // Everything before level i was dropped
+                removed.extend(0..i);
+                break;
+            }
+            let gap = (0..=r)
+                .find(|&gap| {
+                    i > gap
+                        && self.step_ok(report[i - 1 - gap], report[i], sign)
+                        && reachable[i - 1 - gap] & (1 << (r - gap)) != 0
+                })
+                .unwrap();
+            removed.extend((i - gap)..i);
+            i -= 1 + gap;
+            r -= gap;
+        }
+        removed.sort_unstable();
+        Some(removed)
+    }
+
+    // Dynamic programme over the kept levels. `reachable[i]` has bit r set if
+    // level i can be kept after r removals with everything up to it valid.
+    // Each level only looks back past at most `max_removals` dropped levels,
+    // and all removal counts are handled at once by shifting, so a report
+    // costs O(n·k) word operations. Only the last k + 1 masks are kept.
+    // Written with while loops so reports can be checked in a const context
+    const fn is_safe_in(&self, report: &[Level], sign: i32) -> bool {
+        let k = self.max_removals;
+        assert!(k < u32::BITS as usize - 1, "too many removals");
+        let n = report.len();
+        if n <= k + 1 {
+            return true;
+        }
+
+        let mut reachable = [0u32; u32::BITS as usize];
+        let mut i = 0;
+        while i < n {
+            // Dropping every level before this one
+            let mut bits = if i <= k { 1 << i } else { 0 };
+            let mut gap = 0;
+            while gap <= k {
+                let Some(j) = i.checked_sub(gap + 1) else {
+                    break;
+                };
+                if self.step_ok(report[j], report[i], sign) {
+                    bits |= reachable[j % reachable.len()] << gap;
+                }
+                gap += 1;
+            }
+            reachable[i % reachable.len()] = bits & allowed(k);
+
+            // Dropping every level after this one
+            let tail = n - 1 - i;
+            if tail <= k && reachable[i % reachable.len()] & allowed(k - tail) != 0 {
+                return true;
+            }
+            i += 1;
+        }
+
+        false
+    }
+}
+
+// Mask of the removal counts up to and including `removals`
+const fn allowed(removals: usize) -> u32 {
+    (1u32 << (removals + 1)) - 1
+}
+
+pub fn count_safe(reports: &[Report], policy: &SafetyPolicy) -> u16 {
+    reports.iter().filter(|r| policy.is_safe(r)).count() as u16
+}
+
+// A report safe without removals is safe with them, so the dampened check
+// only runs on the reports that fail the strict one
+pub fn solve_both(input: &str) -> (u16, u16) {
+    let reports: AllReports = parse(input).unwrap();
+    let (mut strict, mut dampened) = (0, 0);
+    for report in reports.iter() {
+        if SafetyPolicy::STRICT.is_safe(report) {
+            strict += 1;
+            dampened += 1;
+        } else if SafetyPolicy::DAMPENED.is_safe(report) {
+            dampened += 1;
+        }
+    }
+    (strict, dampened)
+}
+
+// Reads one report from `at`, returning its levels and the index of the
+// start of the next line
+const fn const_parse_report(bytes: &[u8], at: usize) -> ([Level; MAX_LEVELS_PER_REPORT], usize, usize) {
+    let mut report = [0; MAX_LEVELS_PER_REPORT];
+    let mut len = 0;
+    let mut i = skip_blanks(bytes, at);
+    while i < bytes.len() && bytes[i] != b'\n' {
+        let negative = bytes[i] == b'-';
+        if negative {
+            i += 1;
+        }
+        let Some((level, next)) = digits(bytes, i) else {
+            panic!("invalid level");
+        };
+        assert!(level <= Level::MAX as u64, "level out of range");
+        assert!(len < MAX_LEVELS_PER_REPORT, "too many levels");
+        report[len] = if negative { -(level as Level) } else { level as Level };
+        len += 1;
+        i = skip_blanks(bytes, next);
+    }
+    (report, len, i + 1)
+}
+
+pub const fn const_solve(input: &str) -> (u16, u16) {
+    let bytes = input.as_bytes();
+    let (mut strict, mut dampened) = (0, 0);
+    let mut i = 0;
+    while i < bytes.len() {
+        let (report, len, next) = const_parse_report(bytes, i);
+        let levels = report.split_at(len).0;
+        if len > 0 && SafetyPolicy::STRICT.is_safe(levels) {
+            strict += 1;
+            dampened += 1;
+        } else if len > 0 && SafetyPolicy::DAMPENED.is_safe(levels) {
+            dampened += 1;
+        }
+        i = next;
+    }
+    (strict, dampened)
+}
+
+pub const ANSWERS: (u16, u16) = const_solve(INPUT_CONTENT);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::line_ending_variants;
+
+    const EXAMPLE_CONTENT: &str = include_str!("../data/day2/example.txt");
+
+    #[test]
+    fn test_example_answer() {
+        let reports: Vec<Report, 6> = parse(EXAMPLE_CONTENT).unwrap();
+        assert_eq!(count_safe(&reports, &SafetyPolicy::STRICT), 2);
+        assert_eq!(count_safe(&reports, &SafetyPolicy::DAMPENED), 4);
+        assert_eq!(solve_both(EXAMPLE_CONTENT), (2, 4));
+    }
+
+    #[test]
+    fn test_line_endings() {
+        for text in line_ending_variants(EXAMPLE_CONTENT) {
+            assert_eq!(const_solve(&text), (2, 4), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_const_solve() {
+        assert_eq!(const_solve(EXAMPLE_CONTENT), (2, 4));
+        assert_eq!(const_solve("-3 -1 2\n\n1 1\n"), (1, 2));
+    }
+
+    #[test]
+    fn test_policy() {
+        let two = SafetyPolicy { max_removals: 2, ..SafetyPolicy::DAMPENED };
+        assert!(!SafetyPolicy::DAMPENED.is_safe(&[1, 9, 9, 2, 3]));
+        assert!(two.is_safe(&[1, 9, 9, 2, 3]));
+        assert!(two.is_safe(&[9, 9, 1, 2, 3]));
+        assert!(two.is_safe(&[1, 2, 3, 9, 9]));
+        assert!(!two.is_safe(&[1, 9, 2, 9, 3, 9]));
+        assert!(two.is_safe(&[5, 1]));
+        assert!(SafetyPolicy::STRICT.is_safe(&[]));
+
+        let wide = SafetyPolicy { min_step: 2, max_step: 5, max_removals: 0 };
+        assert!(wide.is_safe(&[1, 3, 8, 10]));
+        assert!(!wide.is_safe(&[1, 2, 7]));
+    }
+
+    fn report(levels: &[Level]) -> Report {
+        Report::from_slice(levels).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_report("1 200 -3"), Ok(report(&[1, 200, -3])));
+        assert_eq!(parse_report("1 99999"), Err(ParseErrorKind::LevelOutOfRange));
+        assert_eq!(parse_report("1 x"), Err(ParseErrorKind::InvalidLevel));
+        assert_eq!(parse_report("1 2 3 4 5 6 7 8 9"), Err(ParseErrorKind::TooManyLevels));
+
+        let error = parse::<4>("1 2\n3 4\n5 66666\n").unwrap_err();
+        assert_eq!(error, ParseError { line: 3, kind: ParseErrorKind::LevelOutOfRange });
+        let error = parse::<1>("1 2\n3 4\n").unwrap_err();
+        assert_eq!(error, ParseError { line: 2, kind: ParseErrorKind::TooManyReports });
+    }
+
+    #[test]
+    fn test_classify() {
+        let reports: Vec<Report, 6> = parse(EXAMPLE_CONTENT).unwrap();
+        let verdicts: std::vec::Vec<Verdict> = reports.iter()
+            .map(|r| SafetyPolicy::DAMPENED.classify(r))
+            .collect();
+        assert_eq!(verdicts, [
+            Verdict::Safe(Trend::Decreasing),
+            Verdict::Unsafe { position: 2, fault: Fault::TooLarge },
+            Verdict::Unsafe { position: 3, fault: Fault::TooLarge },
+            Verdict::SafeAfterRemoving { trend: Trend::Increasing, removed: Removed::from_slice(&[1]).unwrap() },
+            Verdict::SafeAfterRemoving { trend: Trend::Decreasing, removed: Removed::from_slice(&[2]).unwrap() },
+            Verdict::Safe(Trend::Increasing),
+        ]);
+
+        let strict = SafetyPolicy::STRICT;
+        assert_eq!(strict.classify(&report(&[1, 3, 2])), Verdict::Unsafe { position: 2, fault: Fault::DirectionChange });
+        assert_eq!(strict.classify(&report(&[4, 4, 5])), Verdict::Unsafe { position: 1, fault: Fault::Zero });
+
+        // Flat steps are fine once min_step allows them
+        let flat = SafetyPolicy { min_step: 0, ..SafetyPolicy::DAMPENED };
+        assert_eq!(flat.classify(&report(&[1, 1, 2])), Verdict::Safe(Trend::Increasing));
+
+        // Dropping the first level flips the trend
+        let verdict = SafetyPolicy::DAMPENED.classify(&report(&[9, 1, 2, 3]));
+        assert_eq!(verdict, Verdict::SafeAfterRemoving { trend: Trend::Increasing, removed: Removed::from_slice(&[0]).unwrap() });
+
+        let two = SafetyPolicy { max_removals: 2, ..SafetyPolicy::DAMPENED };
+        let verdict = two.classify(&report(&[1, 9, 9, 2, 3]));
+        assert_eq!(verdict, Verdict::SafeAfterRemoving { trend: Trend::Increasing, removed: Removed::from_slice(&[1, 2]).unwrap() });
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        // Compare against trying every removal on the real input
+        let reports: std::vec::Vec<Report> = include_str!("../data/day2/input.txt")
+            .lines()
+            .map(|line| parse_report(line).unwrap())
+            .collect();
+        for report in reports {
+            let safe = SafetyPolicy::STRICT.is_safe(&report) || (0..report.len()).any(|i| {
+                let mut removed = report.clone();
+                removed.remove(i);
+                SafetyPolicy::STRICT.is_safe(&removed)
+            });
+            assert_eq!(SafetyPolicy::DAMPENED.is_safe(&report), safe, "{:?}", report);
+            let verdict = SafetyPolicy::DAMPENED.classify(&report);
+            assert_eq!(!matches!(verdict, Verdict::Unsafe { .. }), safe, "{:?}", report);
+            if let Verdict::SafeAfterRemoving { removed, .. } = verdict {
+                let mut kept = report.clone();
+                kept.remove(removed[0]);
+                assert!(SafetyPolicy::STRICT.is_safe(&kept));
+            }
+        }
+    }
+}
@@ -0,0 +1,650 @@
+use core::mem;
+use core::ops::Range;
+use heapless::Vec;
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_while1, take_while_m_n};
+use nom::character::complete::multispace0;
+use nom::character::is_digit;
+use nom::error::{Error, ErrorKind};
+use nom::IResult;
+use crate::util::scan::{self, digits, ScanError};
+
+pub const INPUT_CONTENT: &str = crate::inputs::DAY3;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Grammar {
+    // mul arguments may be any run of digits that fits a u32
+    #[default]
+    Lenient,
+    // mul arguments must be 1-3 digits, as in the puzzle text
+    Strict,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Instruction {
+    Do,
+    Dont,
+    Mult(u32, u32)
+}
+
+// An instruction along with the bytes of the input it was read from
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token {
+    pub span: Range<usize>,
+    pub instruction: Instruction,
+}
+
+fn parse_number(input: &[u8], grammar: Grammar) -> IResult<&[u8], u32> {
+    let span = match grammar {
+        Grammar::Lenient => input,
+        Grammar::Strict => take_while_m_n(1, 3, is_digit)(input)?.1,
+    };
+    match scan::integer::<u32>(span) {
+        Ok((n, rest)) => Ok((&input[span.len() - rest.len()..], n)),
+        Err(ScanError::NoDigits | ScanError::Negative) => {
+            Err(nom::Err::Error(Error::new(input, ErrorKind::Digit)))
+        }
+        Err(ScanError::Overflow) => Err(nom::Err::Error(Error::new(input, ErrorKind::TooLarge)))
+    }
+}
+
+fn parse_mult(input: &[u8], grammar: Grammar) -> IResult<&[u8], Instruction> {
+    let (input, _) = tag("mul(".as_bytes())(input)?;
+    let (input, a) = parse_number(input, grammar)?;
+    let (input, _) = tag(",".as_bytes())(input)?;
+    let (input, b) = parse_number(input, grammar)?;
+    let (input, _) = tag(")".as_bytes())(input)?;
+    Ok((input, Instruction::Mult(a, b)))
+}
+
+fn parse_do(input: &[u8]) -> IResult<&[u8], Instruction> {
+    let (input, _) = tag("do()")(input)?;
+    Ok((input, Instruction::Do))
+}
+
+fn parse_dont(input: &[u8]) -> IResult<&[u8], Instruction> {
+    let (input, _) = tag("don't()")(input)?;
+    Ok((input, Instruction::Dont))
+}
+
+fn parse_instruction(input: &[u8], grammar: Grammar) -> IResult<&[u8], Instruction> {
+    alt((
+        parse_do,
+        parse_dont,
+        |input| parse_mult(input, grammar)
+    ))(input)
+}
+
+// A mul that would be valid apart from whitespace or argument length
+fn parse_loose_mult(input: &[u8]) -> IResult<&[u8], ()> {
+    let (input, _) = tag("mul".as_bytes())(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag("(".as_bytes())(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = take_while1(is_digit)(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag(",".as_bytes())(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = take_while1(is_digit)(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag(")".as_bytes())(input)?;
+    Ok((input, ()))
+}
+
+// Length of the part of a broken mul that still fits the grammar, plus the
+// byte that broke it
+fn broken_mult_len(input: &[u8]) -> usize {
+    let mut i = "mul".len();
+    for expected in [Some(b'('), None, Some(b','), None] {
+        match expected {
+            Some(c) if input.get(i) == Some(&c) => i += 1,
+            None if input.get(i).is_some_and(|&c| is_digit(c)) => {
+                i += input[i..].iter().take_while(|&&c| is_digit(c)).count();
+            }
+            _ => break
+        }
+    }
+    (i + 1).min(input.len())
+}
+
+const LANES_LO: u32 = 0x0101_0101;
+const LANES_HI: u32 = 0x8080_8080;
+
+// Sets the top bit of every byte lane of `x` that is zero. Lanes above a real
+// zero can be flagged by mistake, but the lowest flagged lane is always exact.
+fn zero_lanes(x: u32) -> u32 {
+    x.wrapping_sub(LANES_LO) & !x & LANES_HI
+}
+
+// Every instruction starts with either 'm' or 'd', so only those offsets are
+// worth handing to the parsers. Checks four bytes per step on the M0+.
+fn find_candidate(input: &[u8], from: usize) -> Option<usize> {
+    let mut i = from;
+    while i + 4 <= input.len() {
+        let word = u32::from_le_bytes([input[i], input[i + 1], input[i + 2], input[i + 3]]);
+        let lanes = zero_lanes(word ^ (LANES_LO * b'm' as u32))
+            | zero_lanes(word ^ (LANES_LO * b'd' as u32));
+        if lanes != 0 {
+            return Some(i + (lanes.trailing_zeros() / 8) as usize);
+        }
+        i += 4;
+    }
+    (i..input.len()).find(|&j| matches!(input[j], b'm' | b'd'))
+}
+
+// Walks the corrupted memory yielding every intact instruction
+pub struct Tokens<'a> {
+    input: &'a [u8],
+    pos: usize,
+    grammar: Grammar,
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(start) = find_candidate(self.input, self.pos) {
+            match parse_instruction(&self.input[start..], self.grammar) {
+                Ok((rest, instruction)) => {
+                    self.pos = self.input.len() - rest.len();
+                    return Some(Token { span: start..self.pos, instruction });
+                }
+                Err(_) => self.pos = start + 1
+            }
+        }
+        None
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Corruption {
+    // A mul with whitespace inside it, like `mul ( 2 , 4 )`
+    Whitespace,
+    // A mul with an argument the grammar does not allow, like `mul(1234,5)`
+    ArgumentLength,
+    // A mul with broken brackets or arguments, like `mul(4*` or `mul[3,7]`
+    Malformed,
+    // A `don't` without its `()`
+    MissingParens,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NearMiss {
+    pub span: Range<usize>,
+    pub kind: Corruption,
+}
+
+fn classify_near_miss(fragment: &[u8]) -> Option<(Corruption, usize)> {
+    if fragment.starts_with(b"mul") {
+        match parse_loose_mult(fragment) {
+            Ok((rest, _)) => {
+                let len = fragment.len() - rest.len();
+                if fragment[..len].iter().any(u8::is_ascii_whitespace) {
+                    Some((Corruption::Whitespace, len))
+                } else {
+                    Some((Corruption::ArgumentLength, len))
+                }
+            }
+            Err(_) => Some((Corruption::Malformed, broken_mult_len(fragment)))
+        }
+    } else if fragment.starts_with(b"don't") {
+        Some((Corruption::MissingParens, "don't".len()))
+    } else {
+        None
+    }
+}
+
+// Walks the corrupted memory yielding fragments that look like instructions
+// but are rejected by the grammar
+pub struct NearMisses<'a> {
+    input: &'a [u8],
+    pos: usize,
+    grammar: Grammar,
+}
+
+impl Iterator for NearMisses<'_> {
+    type Item = NearMiss;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(start) = find_candidate(self.input, self.pos) {
+            let fragment = &self.input[start..];
+            if let Ok((rest, _)) = parse_instruction(fragment, self.grammar) {
+                self.pos = self.input.len() - rest.len();
+                continue;
+            }
+            self.pos = start + 1;
+            if let Some((kind, len)) = classify_near_miss(fragment) {
+                return Some(NearMiss { span: start..start + len, kind });
+            }
+        }
+        None
+    }
+}
+
+pub struct Interpreter {
+    conditionals: bool,
+    enabled: bool,
+    sum: u64,
+}
+
+impl Interpreter {
+    pub const fn new() -> Self {
+        Interpreter {
+            conditionals: true,
+            enabled: true,
+            sum: 0
+        }
+    }
+
+    // Every mul counts, do() and don't() have no effect
+    pub const fn ignoring_conditionals() -> Self {
+        Interpreter {
+            conditionals: false,
+            ..Self::new()
+        }
+    }
+
+    pub const fn execute(&mut self, instruction: Instruction) {
+        match instruction {
+            Instruction::Do => self.enabled = true,
+            Instruction::Dont => self.enabled = !self.conditionals,
+            Instruction::Mult(a, b) => if self.enabled {
+                self.sum += a as u64 * b as u64;
+            }
+        }
+    }
+
+    pub const fn sum(&self) -> u64 {
+        self.sum
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Program<'a> {
+    input: &'a [u8],
+    grammar: Grammar,
+}
+
+impl<'a> Program<'a> {
+    pub fn new(input: &'a [u8]) -> Self {
+        Self::with_grammar(input, Grammar::default())
+    }
+
+    pub fn with_grammar(input: &'a [u8], grammar: Grammar) -> Self {
+        Program { input, grammar }
+    }
+
+    pub fn tokens(&self) -> Tokens<'a> {
+        Tokens { input: self.input, pos: 0, grammar: self.grammar }
+    }
+
+    pub fn near_misses(&self) -> NearMisses<'a> {
+        NearMisses { input: self.input, pos: 0, grammar: self.grammar }
+    }
+
+    pub fn run(&self, mut interpreter: Interpreter) -> u64 {
+        for token in self.tokens() {
+            interpreter.execute(token.instruction);
+        }
+        interpreter.sum()
+    }
+}
+
+// Longest instruction the grammar accepts, once any run of leading zeros in
+// a lenient argument is cut down to one
+const fn max_token_len(grammar: Grammar) -> usize {
+    match grammar {
+        Grammar::Lenient => "mul(04294967295,04294967295)".len(),
+        Grammar::Strict => "mul(999,999)".len(),
+    }
+}
+
+// Room for an unfinished token plus enough of the next chunk to complete it
+const CARRY_LEN: usize = 2 * max_token_len(Grammar::Lenient);
+
+// Whether more bytes could still turn `input` into an instruction
+fn is_partial(input: &[u8], grammar: Grammar) -> bool {
+    if b"do()".starts_with(input) || b"don't()".starts_with(input) {
+        return true;
+    }
+    let Some(mut rest) = input.strip_prefix(b"mul(") else {
+        return b"mul(".starts_with(input);
+    };
+    for separator in [b',', b')'] {
+        let len = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+        let fits = match grammar {
+            Grammar::Lenient => len == 0 || digits(rest, 0).is_some_and(|(n, _)| n <= u32::MAX as u64),
+            Grammar::Strict => len <= 3,
+        };
+        match rest.get(len) {
+            None => return fits,
+            Some(&b) if b == separator && len > 0 && fits => rest = &rest[len + 1..],
+            Some(_) => return false
+        }
+    }
+    false
+}
+
+// An unfinished token small enough to carry. Leading zeros do not change a
+// lenient argument, so each run of them is cut down to one.
+fn compact(partial: &[u8], grammar: Grammar) -> Vec<u8, CARRY_LEN> {
+    let mut carry: Vec<u8, CARRY_LEN> = Vec::new();
+    for &b in partial {
+        let leading_zero = grammar == Grammar::Lenient
+            && b == b'0'
+            && (carry.ends_with(b"(0") || carry.ends_with(b",0"));
+        if !leading_zero {
+            carry.push(b).expect("unfinished token longer than any instruction");
+        }
+    }
+    carry
+}
+
+// Runs a program that arrives in chunks, giving the same result as `Program`
+// over the whole input. Bytes that could be the start of a token split across
+// chunks are held back until the next feed.
+pub struct Stream {
+    carry: Vec<u8, CARRY_LEN>,
+    grammar: Grammar,
+    interpreter: Interpreter,
+}
+
+impl Stream {
+    pub fn new(interpreter: Interpreter) -> Self {
+        Self::with_grammar(interpreter, Grammar::default())
+    }
+
+    pub fn with_grammar(interpreter: Interpreter, grammar: Grammar) -> Self {
+        Stream {
+            carry: Vec::new(),
+            grammar,
+            interpreter
+        }
+    }
+
+    pub fn feed(&mut self, mut chunk: &[u8]) {
+        while !self.carry.is_empty() && !chunk.is_empty() {
+            // Join the held back bytes with as much of this chunk as fits
+            let carry_len = self.carry.len();
+            let take = chunk.len().min(CARRY_LEN - carry_len);
+            let mut joined = mem::take(&mut self.carry);
+            let _ = joined.extend_from_slice(&chunk[..take]);
+            let stop = self.scan(&joined, false);
+            if stop >= carry_len {
+                chunk = &chunk[stop - carry_len..];
+                break;
+            }
+            // The token held back is still unfinished, carry it on with
+            // whatever was taken
+            self.carry = compact(&joined[stop..], self.grammar);
+            chunk = &chunk[take..];
+        }
+
+        if !chunk.is_empty() {
+            let stop = self.scan(chunk, false);
+            self.carry = compact(&chunk[stop..], self.grammar);
+        }
+    }
+
+    pub fn finish(mut self) -> u64 {
+        let carry = mem::take(&mut self.carry);
+        self.scan(&carry, true);
+        self.interpreter.sum()
+    }
+
+    // Executes every instruction in `input` and returns where scanning stopped.
+    // Unless this is the last of the input, a failed parse that runs into the
+    // end might just be missing bytes, so scanning stops there.
+    fn scan(&mut self, input: &[u8], last: bool) -> usize {
+        let mut pos = 0;
+        while let Some(start) = find_candidate(input, pos) {
+            match parse_instruction(&input[start..], self.grammar) {
+                Ok((rest, instruction)) => {
+                    self.interpreter.execute(instruction);
+                    pos = input.len() - rest.len();
+                }
+                Err(_) if !last && is_partial(&input[start..], self.grammar) => return start,
+                Err(_) => pos = start + 1
+            }
+        }
+        input.len()
+    }
+}
+
+// One pass over the tokens feeds both interpreters
+pub fn solve_both(input: &str) -> (u64, u64) {
+    let mut all = Interpreter::ignoring_conditionals();
+    let mut conditional = Interpreter::new();
+    for token in Program::new(input.as_bytes()).tokens() {
+        all.execute(token.instruction);
+        conditional.execute(token.instruction);
+    }
+    (all.sum(), conditional.sum())
+}
+
+const fn starts_with(input: &[u8], at: usize, prefix: &[u8]) -> bool {
+    if input.len() - at < prefix.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < prefix.len() {
+        if input[at + i] != prefix[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+// A lenient mul argument, the same as `parse_number` accepts
+const fn const_number(input: &[u8], at: usize) -> Option<(u32, usize)> {
+    match digits(input, at) {
+        Some((n, next)) if n <= u32::MAX as u64 => Some((n as u32, next)),
+        _ => None
+    }
+}
+
+// The instruction at `at` and the index past it, tried in the same order as
+// `parse_instruction`
+const fn const_instruction(input: &[u8], at: usize) -> Option<(Instruction, usize)> {
+    if starts_with(input, at, b"do()") {
+        return Some((Instruction::Do, at + 4));
+    }
+    if starts_with(input, at, b"don't()") {
+        return Some((Instruction::Dont, at + 7));
+    }
+    if !starts_with(input, at, b"mul(") {
+        return None;
+    }
+    let Some((a, i)) = const_number(input, at + 4) else {
+        return None;
+    };
+    if !starts_with(input, i, b",") {
+        return None;
+    }
+    let Some((b, i)) = const_number(input, i + 1) else {
+        return None;
+    };
+    if !starts_with(input, i, b")") {
+        return None;
+    }
+    Some((Instruction::Mult(a, b), i + 1))
+}
+
+// Both parts in a const context, with the lenient grammar
+pub const fn const_solve(input: &str) -> (u64, u64) {
+    let input = input.as_bytes();
+    let mut all = Interpreter::ignoring_conditionals();
+    let mut conditional = Interpreter::new();
+    let mut i = 0;
+    while i < input.len() {
+        match const_instruction(input, i) {
+            Some((instruction, next)) => {
+                all.execute(instruction);
+                conditional.execute(instruction);
+                i = next;
+            }
+            None => i += 1
+        }
+    }
+    (all.sum(), conditional.sum())
+}
+
+pub const ANSWERS: (u64, u64) = const_solve(INPUT_CONTENT);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_CONTENT: &str = include_str!("../data/day3/example.txt");
+    const EXAMPLE_B_CONTENT: &str = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    #[test]
+    fn test_find_candidate() {
+        let input = b"xx%&[m]!d@^_+m(";
+        assert_eq!(find_candidate(input, 0), Some(5));
+        assert_eq!(find_candidate(input, 6), Some(8));
+        assert_eq!(find_candidate(input, 9), Some(13));
+        assert_eq!(find_candidate(input, 14), None);
+        assert_eq!(find_candidate(b"d", 0), Some(0));
+        assert_eq!(find_candidate(b"", 0), None);
+
+        // Compare against a plain byte search over the whole input
+        let input = include_bytes!("../data/day3/input.txt");
+        let mut pos = 0;
+        while let Some(i) = find_candidate(input, pos) {
+            assert_eq!(Some(i), (pos..input.len()).find(|&j| matches!(input[j], b'm' | b'd')));
+            pos = i + 1;
+        }
+        assert!(input[pos..].iter().all(|&b| b != b'm' && b != b'd'));
+    }
+
+    #[test]
+    fn test_tokens() {
+        let program = Program::new(EXAMPLE_B_CONTENT.as_bytes());
+        let tokens: std::vec::Vec<Token> = program.tokens().collect();
+        assert_eq!(tokens, [
+            Token { span: 1..9, instruction: Instruction::Mult(2, 4) },
+            Token { span: 20..27, instruction: Instruction::Dont },
+            Token { span: 28..36, instruction: Instruction::Mult(5, 5) },
+            Token { span: 48..57, instruction: Instruction::Mult(11, 8) },
+            Token { span: 59..63, instruction: Instruction::Do },
+            Token { span: 64..72, instruction: Instruction::Mult(8, 5) },
+        ]);
+    }
+
+    #[test]
+    fn test_grammar() {
+        let input = b"mul(1234,5)mul(12,345)mul(99999999999,1)";
+        let lenient: std::vec::Vec<Instruction> = Program::new(input)
+            .tokens()
+            .map(|t| t.instruction)
+            .collect();
+        assert_eq!(lenient, [Instruction::Mult(1234, 5), Instruction::Mult(12, 345)]);
+
+        let strict: std::vec::Vec<Instruction> = Program::with_grammar(input, Grammar::Strict)
+            .tokens()
+            .map(|t| t.instruction)
+            .collect();
+        assert_eq!(strict, [Instruction::Mult(12, 345)]);
+    }
+
+    #[test]
+    fn test_near_misses() {
+        let input = b"mul(4*mul ( 2 , 4 )don'tmul(1234,5)mul[3,7]mul(2,3)";
+        let near_misses: std::vec::Vec<NearMiss> = Program::with_grammar(input, Grammar::Strict)
+            .near_misses()
+            .collect();
+        assert_eq!(near_misses, [
+            NearMiss { span: 0..6, kind: Corruption::Malformed },
+            NearMiss { span: 6..19, kind: Corruption::Whitespace },
+            NearMiss { span: 19..24, kind: Corruption::MissingParens },
+            NearMiss { span: 24..35, kind: Corruption::ArgumentLength },
+            NearMiss { span: 35..39, kind: Corruption::Malformed },
+        ]);
+
+        let program = Program::new(EXAMPLE_CONTENT.as_bytes());
+        let kinds: std::vec::Vec<(usize, Corruption)> = program.near_misses()
+            .map(|m| (m.span.start, m.kind))
+            .collect();
+        assert_eq!(kinds, [(11, Corruption::Malformed), (38, Corruption::Malformed)]);
+    }
+
+    fn run_stream(input: &[u8], chunk_len: usize, interpreter: Interpreter, grammar: Grammar) -> u64 {
+        let mut stream = Stream::with_grammar(interpreter, grammar);
+        for chunk in input.chunks(chunk_len) {
+            stream.feed(chunk);
+        }
+        stream.finish()
+    }
+
+    #[test]
+    fn test_stream() {
+        let input = EXAMPLE_B_CONTENT.as_bytes();
+        for chunk_len in 1..=input.len() {
+            assert_eq!(run_stream(input, chunk_len, Interpreter::new(), Grammar::Lenient), 48);
+            assert_eq!(run_stream(input, chunk_len, Interpreter::ignoring_conditionals(), Grammar::Strict), 161);
+        }
+
+        let input = b"mul(99999999,2)mul(1234,2)mul(123,2)";
+        for chunk_len in 1..=input.len() {
+            assert_eq!(run_stream(input, chunk_len, Interpreter::new(), Grammar::Lenient), 199999998 + 2468 + 246);
+            assert_eq!(run_stream(input, chunk_len, Interpreter::new(), Grammar::Strict), 246);
+        }
+    }
+
+    #[test]
+    fn test_stream_leading_zeros() {
+        let zeros = "0".repeat(100);
+        let input = std::format!("xmul({z}7,{z}6)mul({z}4294967296,1)mul(2,{z})do()mul(0{z},3)", z = zeros);
+        let input = input.as_bytes();
+        for grammar in [Grammar::Lenient, Grammar::Strict] {
+            let expected = Program::with_grammar(input, grammar).run(Interpreter::new());
+            for chunk_len in 1..=input.len() {
+                assert_eq!(run_stream(input, chunk_len, Interpreter::new(), grammar), expected, "{}", chunk_len);
+            }
+        }
+        assert_eq!(Program::new(input).run(Interpreter::new()), 42);
+    }
+
+    #[test]
+    fn test_stream_input() {
+        let input = include_bytes!("../data/day3/input.txt");
+        for grammar in [Grammar::Lenient, Grammar::Strict] {
+            let expected = Program::with_grammar(input, grammar).run(Interpreter::new());
+            for chunk_len in [1, 2, 3, 5, 7, 12, 13, 26, 64, 1000] {
+                assert_eq!(run_stream(input, chunk_len, Interpreter::new(), grammar), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_example_answer() {
+        let program = Program::new(EXAMPLE_CONTENT.as_bytes());
+        assert_eq!(program.run(Interpreter::ignoring_conditionals()), 161);
+        let program = Program::new(EXAMPLE_B_CONTENT.as_bytes());
+        assert_eq!(program.run(Interpreter::ignoring_conditionals()), 161);
+        assert_eq!(program.run(Interpreter::new()), 48);
+        assert_eq!(solve_both(EXAMPLE_B_CONTENT), (161, 48));
+    }
+
+    #[test]
+    fn test_wide_products() {
+        let input = "mul(70000,70000)don't()mul(4294967295,4294967295)";
+        assert_eq!(Program::new(input.as_bytes()).run(Interpreter::new()), 4_900_000_000);
+        assert_eq!(solve_both(input), (4_900_000_000 + 18_446_744_065_119_617_025, 4_900_000_000));
+        assert_eq!(const_solve(input), solve_both(input));
+    }
+
+    #[test]
+    fn test_const_solve() {
+        assert_eq!(const_solve(EXAMPLE_B_CONTENT), (161, 48));
+        let input = "mul(4294967295,0)mul(4294967296,1)mul(1 ,2)do()don't()mul(2,3)do()mul(00012,2";
+        assert_eq!(const_solve(input), solve_both(input));
+    }
+}
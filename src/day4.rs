@@ -0,0 +1,943 @@
+use core::fmt;
+use heapless::Vec;
+use crate::util::grid::{self, grid_shape, MisalignedRow};
+use crate::util::aho_corasick::{AhoCorasick, BuildError, Builder};
+
+pub const INPUT_CONTENT: &str = crate::inputs::DAY4;
+
+pub const XMAS: &str = "XMAS";
+pub const X_MAS: &str = "\
+M.S
+.A.
+M.S";
+pub const X_MAS_TEMPLATE: Template<5> = Template::parse(X_MAS).unwrap();
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    East, West, South, North, SouthEast, NorthEast, NorthWest, SouthWest
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::East, Direction::West, Direction::South, Direction::North,
+        Direction::SouthEast, Direction::NorthEast, Direction::NorthWest, Direction::SouthWest
+    ];
+
+    // Step taken in (x, y), where y grows down the grid
+    pub const fn step(self) -> (isize, isize) {
+        match self {
+            Direction::East => (1, 0),
+            Direction::West => (-1, 0),
+            Direction::South => (0, 1),
+            Direction::North => (0, -1),
+            Direction::SouthEast => (1, 1),
+            Direction::NorthEast => (1, -1),
+            Direction::NorthWest => (-1, -1),
+            Direction::SouthWest => (-1, 1)
+        }
+    }
+
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+            Direction::South => Direction::North,
+            Direction::North => Direction::South,
+            Direction::SouthEast => Direction::NorthWest,
+            Direction::NorthEast => Direction::SouthWest,
+            Direction::NorthWest => Direction::SouthEast,
+            Direction::SouthWest => Direction::NorthEast
+        }
+    }
+}
+
+// Anything letters can be read from by position
+pub trait Cells {
+    fn get(&self, x: usize, y: usize) -> Option<u8>;
+
+    fn get_offset(&self, x: usize, y: usize, dx: isize, dy: isize) -> Option<u8> {
+        let x = x.checked_add_signed(dx)?;
+        let y = y.checked_add_signed(dy)?;
+        self.get(x, y)
+    }
+
+    fn word_at(&self, x: usize, y: usize, direction: Direction, word: &[u8]) -> bool {
+        let (dx, dy) = direction.step();
+        word.iter().enumerate().all(|(i, &c)| {
+            let i = i as isize;
+            self.get_offset(x, y, i * dx, i * dy) == Some(c)
+        })
+    }
+}
+
+// The puzzle's letters, read in place from the input
+#[derive(Copy, Clone, Debug)]
+pub struct Grid<'a> {
+    letters: grid::Grid<'a>,
+}
+
+impl<'a> Grid<'a> {
+    pub fn parse(text: &'a str) -> Result<Self, MisalignedRow> {
+        Ok(Grid { letters: grid::Grid::parse(text)? })
+    }
+
+    pub fn from_cells(cells: &'a [u8], width: usize) -> Self {
+        Grid { letters: grid::Grid::from_cells(cells, width) }
+    }
+
+    pub fn width(&self) -> usize {
+        self.letters.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.letters.height()
+    }
+
+    // Start of every row, column and diagonal, with the direction it runs in
+    fn lines(&self) -> impl Iterator<Item = (usize, usize, Direction)> {
+        let (width, height) = (self.width(), self.height());
+        let rows = (0..height).map(|y| (0, y, Direction::East));
+        let columns = (0..width).map(|x| (x, 0, Direction::South));
+        let diagonals = (0..width).map(|x| (x, 0, Direction::SouthEast))
+            .chain((1..height).map(|y| (0, y, Direction::SouthEast)));
+        let anti_diagonals = (0..width).map(|x| (x, 0, Direction::SouthWest))
+            .chain((1..height).map(move |y| (width.saturating_sub(1), y, Direction::SouthWest)));
+        rows.chain(columns).chain(diagonals).chain(anti_diagonals)
+    }
+
+    fn line_cells(&self, x: usize, y: usize, direction: Direction) -> impl Iterator<Item = u8> + '_ {
+        let (dx, dy) = direction.step();
+        (0..).map_while(move |i: isize| self.get_offset(x, y, i * dx, i * dy))
+    }
+
+    pub fn find_word<'w>(&self, word: &'w str) -> WordMatches<'a, 'w> {
+        WordMatches { grid: *self, word: word.as_bytes(), x: 0, y: 0, d: 0 }
+    }
+
+    pub fn count_word(&self, word: &str) -> u32 {
+        self.find_word(word).count() as u32
+    }
+
+    pub fn find_template<const N: usize>(&self, template: &Template<N>) -> TemplateMatches<'a, N> {
+        TemplateMatches { grid: *self, variants: template.variants(), x: 0, y: 0, v: 0 }
+    }
+
+    pub fn count_template<const N: usize>(&self, template: &Template<N>) -> u32 {
+        self.find_template(template).count() as u32
+    }
+
+    // M must hold a bit per cell, so at least width * height / 32 words
+    pub fn highlight_word<const M: usize>(&self, word: &str) -> Highlight<'a, M> {
+        let mut highlight = Highlight::new(*self);
+        for m in self.find_word(word) {
+            let (dx, dy) = m.direction.step();
+            for i in 0..word.len() as isize {
+                highlight.mark((m.x as isize + i * dx) as usize, (m.y as isize + i * dy) as usize);
+            }
+        }
+        highlight
+    }
+
+    pub fn highlight_template<const M: usize, const N: usize>(&self, template: &Template<N>) -> Highlight<'a, M> {
+        let mut highlight = Highlight::new(*self);
+        let variants = template.variants();
+        for m in self.find_template(template) {
+            for &(dx, dy, _) in variants[m.variant].cells() {
+                highlight.mark(m.x + dx, m.y + dy);
+            }
+        }
+        highlight
+    }
+}
+
+// A grid with every letter that is not part of a match shown as '.', like the
+// examples in the puzzle text. M words hold one bit per cell.
+pub struct Highlight<'a, const M: usize> {
+    grid: Grid<'a>,
+    marked: [u32; M],
+}
+
+impl<'a, const M: usize> Highlight<'a, M> {
+    pub fn new(grid: Grid<'a>) -> Self {
+        assert!(grid.width() * grid.height() <= M * 32, "grid has more cells than the mask");
+        Highlight { grid, marked: [0; M] }
+    }
+
+    pub fn mark(&mut self, x: usize, y: usize) {
+        let i = y * self.grid.width() + x;
+        self.marked[i / 32] |= 1 << (i % 32);
+    }
+
+    pub fn is_marked(&self, x: usize, y: usize) -> bool {
+        let i = y * self.grid.width() + x;
+        self.marked[i / 32] & (1 << (i % 32)) != 0
+    }
+
+    fn cell(&self, x: usize, y: usize) -> char {
+        match self.grid.get(x, y) {
+            Some(c) if self.is_marked(x, y) => c as char,
+            _ => '.'
+        }
+    }
+}
+
+impl<const M: usize> fmt::Display for Highlight<'_, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..self.grid.height() {
+            for x in 0..self.grid.width() {
+                write!(f, "{}", self.cell(x, y))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const M: usize> defmt::Format for Highlight<'_, M> {
+    fn format(&self, f: defmt::Formatter) {
+        for y in 0..self.grid.height() {
+            for x in 0..self.grid.width() {
+                defmt::write!(f, "{=char}", self.cell(x, y));
+            }
+            defmt::write!(f, "\n");
+        }
+    }
+}
+
+impl Cells for Grid<'_> {
+    fn get(&self, x: usize, y: usize) -> Option<u8> {
+        self.letters.get(x, y)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WordMatch {
+    pub x: usize,
+    pub y: usize,
+    pub direction: Direction,
+}
+
+pub struct WordMatches<'a, 'w> {
+    grid: Grid<'a>,
+    word: &'w [u8],
+    x: usize,
+    y: usize,
+    d: usize,
+}
+
+impl Iterator for WordMatches<'_, '_> {
+    type Item = WordMatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.y < self.grid.height() {
+            if self.d == Direction::ALL.len() {
+                self.d = 0;
+                self.x += 1;
+                if self.x == self.grid.width() {
+                    self.x = 0;
+                    self.y += 1;
+                }
+                continue;
+            }
+
+            let direction = Direction::ALL[self.d];
+            self.d += 1;
+            if self.grid.word_at(self.x, self.y, direction, self.word) {
+                return Some(WordMatch { x: self.x, y: self.y, direction });
+            }
+        }
+        None
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum PatternKind {
+    Forward, Reversed, Palindrome
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DictionaryMatch {
+    pub word: usize,
+    pub x: usize,
+    pub y: usize,
+    pub direction: Direction,
+}
+
+// Searches for many words at once by streaming every line of the grid through
+// a single automaton. Words are also added reversed so each line only has to
+// be read one way, which takes two of the P patterns unless the word is a
+// palindrome.
+pub struct Dictionary<const S: usize, const P: usize> {
+    automaton: AhoCorasick<S, P>,
+    patterns: Vec<(usize, PatternKind), P>,
+    words: usize,
+}
+
+impl<const S: usize, const P: usize> Dictionary<S, P> {
+    pub fn new(words: &[&str]) -> Result<Self, BuildError> {
+        let mut builder = Builder::new();
+        let mut patterns = Vec::new();
+        for (i, word) in words.iter().enumerate() {
+            if word.bytes().eq(word.bytes().rev()) {
+                builder.insert(word.bytes())?;
+                let _ = patterns.push((i, PatternKind::Palindrome));
+            } else {
+                builder.insert(word.bytes())?;
+                let _ = patterns.push((i, PatternKind::Forward));
+                builder.insert(word.bytes().rev())?;
+                let _ = patterns.push((i, PatternKind::Reversed));
+            }
+        }
+
+        Ok(Dictionary { automaton: builder.build(), patterns, words: words.len() })
+    }
+
+    pub fn for_each_match<F: FnMut(DictionaryMatch)>(&self, grid: &Grid, mut f: F) {
+        for (x, y, direction) in grid.lines() {
+            let (dx, dy) = direction.step();
+            let cell = |i: usize| {
+                let i = i as isize;
+                ((x as isize + i * dx) as usize, (y as isize + i * dy) as usize)
+            };
+
+            for m in self.automaton.find_iter(grid.line_cells(x, y, direction)) {
+                let (word, kind) = self.patterns[m.pattern];
+                let first = cell(m.end - self.automaton.pattern_len(m.pattern));
+                let last = cell(m.end - 1);
+                if kind != PatternKind::Reversed {
+                    f(DictionaryMatch { word, x: first.0, y: first.1, direction });
+                }
+                if kind != PatternKind::Forward {
+                    f(DictionaryMatch { word, x: last.0, y: last.1, direction: direction.opposite() });
+                }
+            }
+        }
+    }
+
+    // Number of times each word appears, in the order they were given
+    pub fn count(&self, grid: &Grid) -> Vec<u32, P> {
+        let mut counts = Vec::new();
+        let _ = counts.resize(self.words, 0);
+        self.for_each_match(grid, |m| counts[m.word] += 1);
+        counts
+    }
+}
+
+// A 2D pattern of letters, where '.' marks a cell that matches anything.
+// The first `len` cells are kept sorted and the rest left empty, so two
+// templates covering the same cells compare equal.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Template<const N: usize> {
+    width: usize,
+    height: usize,
+    cells: [(usize, usize, u8); N],
+    len: usize,
+}
+
+impl<const N: usize> Template<N> {
+    // Returns None if the block has more than N letters
+    pub const fn parse(text: &str) -> Option<Self> {
+        let bytes = text.as_bytes();
+        let mut template = Template { width: 0, height: 0, cells: [(0, 0, 0); N], len: 0 };
+        let (mut x, mut y) = (0, 0);
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\n' => {
+                    x = 0;
+                    y += 1;
+                }
+                b'\r' => {}
+                c => {
+                    if c != b'.' {
+                        if template.len == N {
+                            return None;
+                        }
+                        template.cells[template.len] = (x, y, c);
+                        template.len += 1;
+                    }
+                    x += 1;
+                    if x > template.width {
+                        template.width = x;
+                    }
+                }
+            }
+            i += 1;
+        }
+        // Counted like str::lines, where a final newline starts no new row
+        template.height = match bytes.last() {
+            Some(b'\n') => y,
+            Some(_) => y + 1,
+            None => 0
+        };
+        template.sort();
+        Some(template)
+    }
+
+    // Insertion sort on position, which no two cells share
+    const fn sort(&mut self) {
+        let mut i = 1;
+        while i < self.len {
+            let mut j = i;
+            while j > 0 && {
+                let ((ax, ay, _), (bx, by, _)) = (self.cells[j - 1], self.cells[j]);
+                ax > bx || (ax == bx && ay > by)
+            } {
+                self.cells.swap(j - 1, j);
+                j -= 1;
+            }
+            i += 1;
+        }
+    }
+
+    pub const fn cells(&self) -> &[(usize, usize, u8)] {
+        self.cells.split_at(self.len).0
+    }
+
+    // Quarter turn clockwise
+    const fn rotate(&self) -> Self {
+        let mut t = Template { width: self.height, height: self.width, ..*self };
+        let mut i = 0;
+        while i < self.len {
+            let (x, y, c) = self.cells[i];
+            t.cells[i] = (self.height - 1 - y, x, c);
+            i += 1;
+        }
+        t.sort();
+        t
+    }
+
+    // Mirror left to right
+    const fn reflect(&self) -> Self {
+        let mut t = *self;
+        let mut i = 0;
+        while i < self.len {
+            let (x, y, c) = self.cells[i];
+            t.cells[i] = (self.width - 1 - x, y, c);
+            i += 1;
+        }
+        t.sort();
+        t
+    }
+
+    // PartialEq for const contexts
+    const fn same(&self, other: &Self) -> bool {
+        if self.width != other.width || self.height != other.height || self.len != other.len {
+            return false;
+        }
+        let mut i = 0;
+        while i < self.len {
+            let ((ax, ay, ac), (bx, by, bc)) = (self.cells[i], other.cells[i]);
+            if ax != bx || ay != by || ac != bc {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    // Every distinct rotation and reflection, starting with the template
+    // itself. Only the first of the returned count are filled in.
+    pub const fn variant_array(&self) -> ([Self; 8], usize) {
+        let mut variants = [*self; 8];
+        let mut count = 0;
+        let mut start = 0;
+        while start < 2 {
+            let mut t = if start == 0 { *self } else { self.reflect() };
+            let mut turn = 0;
+            while turn < 4 {
+                let mut seen = false;
+                let mut v = 0;
+                while v < count {
+                    seen |= variants[v].same(&t);
+                    v += 1;
+                }
+                if !seen {
+                    variants[count] = t;
+                    count += 1;
+                }
+                t = t.rotate();
+                turn += 1;
+            }
+            start += 1;
+        }
+        (variants, count)
+    }
+
+    pub fn variants(&self) -> Vec<Self, 8> {
+        let (variants, count) = self.variant_array();
+        Vec::from_slice(&variants[..count]).unwrap()
+    }
+
+    // Columns and rows between the top left of the template and its last
+    // letter either way
+    const fn extent(&self) -> (usize, usize) {
+        let (mut columns, mut rows) = (0, 0);
+        let mut i = 0;
+        while i < self.len {
+            let (x, y, _) = self.cells[i];
+            if x + 1 > columns {
+                columns = x + 1;
+            }
+            if y + 1 > rows {
+                rows = y + 1;
+            }
+            i += 1;
+        }
+        (columns, rows)
+    }
+
+    fn rows(&self) -> usize {
+        self.extent().1
+    }
+
+    pub fn matches_at<C: Cells>(&self, cells: &C, x: usize, y: usize) -> bool {
+        self.cells().iter().all(|&(dx, dy, c)| cells.get(x + dx, y + dy) == Some(c))
+    }
+
+    // Same as matches_at, on a grid laid out like the puzzle text with room
+    // for every letter of the template at (x, y)
+    const fn matches_text(&self, bytes: &[u8], stride: usize, x: usize, y: usize) -> bool {
+        let mut i = 0;
+        while i < self.len {
+            let (dx, dy, c) = self.cells[i];
+            if bytes[(y + dy) * stride + x + dx] != c {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+}
+
+// Position of the top left corner of the matching variant, along with the
+// cell in the middle of it
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TemplateMatch {
+    pub x: usize,
+    pub y: usize,
+    pub variant: usize,
+    pub centre: (usize, usize),
+}
+
+pub struct TemplateMatches<'a, const N: usize> {
+    grid: Grid<'a>,
+    variants: Vec<Template<N>, 8>,
+    x: usize,
+    y: usize,
+    v: usize,
+}
+
+impl<const N: usize> Iterator for TemplateMatches<'_, N> {
+    type Item = TemplateMatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.y < self.grid.height() {
+            if self.v == self.variants.len() {
+                self.v = 0;
+                self.x += 1;
+                if self.x == self.grid.width() {
+                    self.x = 0;
+                    self.y += 1;
+                }
+                continue;
+            }
+
+            let variant = self.v;
+            let t = &self.variants[variant];
+            self.v += 1;
+            if t.matches_at(&self.grid, self.x, self.y) {
+                let centre = (self.x + (t.width - 1) / 2, self.y + (t.height - 1) / 2);
+                return Some(TemplateMatch { x: self.x, y: self.y, variant, centre });
+            }
+        }
+        None
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LineTooLong;
+
+// The last H rows of a grid that arrives a line at a time, each row holding
+// up to W letters. Rows keep their position in the whole grid.
+pub struct RowWindow<const W: usize, const H: usize> {
+    rows: [[u8; W]; H],
+    width: usize,
+    pushed: usize,
+}
+
+impl<const W: usize, const H: usize> RowWindow<W, H> {
+    pub fn new() -> Self {
+        RowWindow { rows: [[0; W]; H], width: 0, pushed: 0 }
+    }
+
+    pub fn push(&mut self, line: &[u8]) -> Result<(), LineTooLong> {
+        if line.len() > W {
+            return Err(LineTooLong);
+        }
+        let row = &mut self.rows[self.pushed % H];
+        row[..line.len()].copy_from_slice(line);
+        row[line.len()..].fill(0);
+        self.width = self.width.max(line.len());
+        self.pushed += 1;
+        Ok(())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    // Position of the newest row, if there is one
+    pub fn last_row(&self) -> Option<usize> {
+        self.pushed.checked_sub(1)
+    }
+}
+
+impl<const W: usize, const H: usize> Default for RowWindow<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize> Cells for RowWindow<W, H> {
+    fn get(&self, x: usize, y: usize) -> Option<u8> {
+        if x >= self.width || y >= self.pushed || y + H < self.pushed {
+            None
+        } else {
+            Some(self.rows[y % H][x])
+        }
+    }
+}
+
+// Counts a word as the grid streams in, only keeping as many rows as the word
+// is long. Each match is counted once the lowest row it touches arrives.
+pub struct WordStream<'w, const W: usize, const H: usize> {
+    window: RowWindow<W, H>,
+    word: &'w [u8],
+    count: u32,
+}
+
+impl<'w, const W: usize, const H: usize> WordStream<'w, W, H> {
+    pub fn new(word: &'w str) -> Self {
+        assert!(word.len() <= H, "word is taller than the window");
+        WordStream { window: RowWindow::new(), word: word.as_bytes(), count: 0 }
+    }
+
+    pub fn push_line(&mut self, line: &[u8]) -> Result<(), LineTooLong> {
+        self.window.push(line)?;
+        let row = self.window.pushed - 1;
+        let below = self.word.len().saturating_sub(1);
+        for x in 0..self.window.width {
+            for direction in Direction::ALL {
+                let y = match direction.step() {
+                    (_, 1) if row < below => continue,
+                    (_, 1) => row - below,
+                    _ => row
+                };
+                self.count += self.window.word_at(x, y, direction, self.word) as u32;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+// Counts a template as the grid streams in, keeping H rows
+pub struct TemplateStream<const W: usize, const H: usize, const N: usize> {
+    window: RowWindow<W, H>,
+    variants: Vec<Template<N>, 8>,
+    count: u32,
+}
+
+impl<const W: usize, const H: usize, const N: usize> TemplateStream<W, H, N> {
+    pub fn new(template: &Template<N>) -> Self {
+        assert!(template.rows() <= H, "template is taller than the window");
+        TemplateStream { window: RowWindow::new(), variants: template.variants(), count: 0 }
+    }
+
+    pub fn push_line(&mut self, line: &[u8]) -> Result<(), LineTooLong> {
+        self.window.push(line)?;
+        let row = self.window.pushed - 1;
+        for variant in self.variants.iter() {
+            let Some(top) = (row + 1).checked_sub(variant.rows()) else {
+                continue;
+            };
+            for x in 0..self.window.width {
+                self.count += variant.matches_at(&self.window, x, top) as u32;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+// Both searches over one parse of the grid
+pub fn solve_both(input: &str) -> (u32, u32) {
+    let grid = Grid::parse(input).unwrap();
+    (grid.count_word(XMAS), grid.count_template(&X_MAS_TEMPLATE))
+}
+
+const fn const_grid_shape(text: &str) -> (usize, usize, usize) {
+    match grid_shape(text) {
+        Ok(shape) => shape,
+        Err(_) => panic!("rows of the grid do not line up")
+    }
+}
+
+// Cell (x, y) of a grid laid out like the puzzle text, 0 outside it
+const fn const_cell(bytes: &[u8], (width, height, stride): (usize, usize, usize), x: isize, y: isize) -> u8 {
+    if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+        0
+    } else {
+        bytes[y as usize * stride + x as usize]
+    }
+}
+
+pub const fn const_count_word(text: &str, word: &str) -> u32 {
+    let (bytes, word) = (text.as_bytes(), word.as_bytes());
+    let shape = const_grid_shape(text);
+    let (width, height, stride) = shape;
+    let mut count = 0;
+    let mut i = 0;
+    while i < width * height {
+        let (x, y) = ((i % width) as isize, (i / width) as isize);
+        // Only cells holding the first letter can start a match
+        if !word.is_empty() && bytes[(i / width) * stride + i % width] != word[0] {
+            i += 1;
+            continue;
+        }
+        let mut d = 0;
+        while d < Direction::ALL.len() {
+            let (dx, dy) = Direction::ALL[d].step();
+            let mut n = 0;
+            while n < word.len() && const_cell(bytes, shape, x + n as isize * dx, y + n as isize * dy) == word[n] {
+                n += 1;
+            }
+            if n == word.len() {
+                count += 1;
+            }
+            d += 1;
+        }
+        i += 1;
+    }
+    count
+}
+
+// Finds the same matches as Grid::count_template
+pub const fn const_count_template<const N: usize>(text: &str, template: &Template<N>) -> u32 {
+    let bytes = text.as_bytes();
+    let (width, height, stride) = const_grid_shape(text);
+    let (variants, len) = template.variant_array();
+    let mut count = 0;
+    let mut v = 0;
+    while v < len {
+        let t = &variants[v];
+        let (columns, rows) = t.extent();
+        let mut y = 0;
+        while y + rows <= height {
+            let mut x = 0;
+            while x + columns <= width {
+                count += t.matches_text(bytes, stride, x, y) as u32;
+                x += 1;
+            }
+            y += 1;
+        }
+        v += 1;
+    }
+    count
+}
+
+pub const fn const_solve(input: &str) -> (u32, u32) {
+    (const_count_word(input, XMAS), const_count_template(input, &X_MAS_TEMPLATE))
+}
+
+pub const ANSWERS: (u32, u32) = const_solve(INPUT_CONTENT);
+
+#[cfg(feature = "preparsed")]
+pub fn solve_preparsed() -> (u32, u32) {
+    use crate::preparsed::day4::{GRID, WIDTH};
+    let grid = Grid::from_cells(&GRID, WIDTH);
+    (grid.count_word(XMAS), grid.count_template(&X_MAS_TEMPLATE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::line_ending_variants;
+
+    const EXAMPLE_CONTENT: &str = include_str!("../data/day4/example.txt");
+
+    #[test]
+    fn test_grid() {
+        let grid = Grid::parse(EXAMPLE_CONTENT).unwrap();
+        assert_eq!((grid.width(), grid.height()), (10, 10));
+        assert_eq!(grid.get(0, 0), Some(b'M'));
+        assert_eq!(grid.get(9, 9), Some(b'X'));
+        assert_eq!(grid.get(10, 0), None);
+        assert!(grid.word_at(5, 0, Direction::East, b"XMAS"));
+        assert!(grid.word_at(4, 1, Direction::West, b"XMAS"));
+    }
+
+    #[test]
+    fn test_find_word() {
+        let grid = Grid::parse(EXAMPLE_CONTENT).unwrap();
+        let first = grid.find_word("XMAS").next();
+        assert_eq!(first, Some(WordMatch { x: 4, y: 0, direction: Direction::SouthEast }));
+        assert_eq!(grid.count_word("XMAS"), 18);
+        assert_eq!(grid.count_word("SAMX"), 18);
+        assert_eq!(grid.count_word("X"), 19 * 8);
+    }
+
+    #[test]
+    fn test_dictionary() {
+        let grid = Grid::parse(EXAMPLE_CONTENT).unwrap();
+        let words = ["XMAS", "MAS", "A", "AMA", "XX", "SAMX"];
+        let dictionary: Dictionary<64, 16> = Dictionary::new(&words).unwrap();
+        let counts = dictionary.count(&grid);
+        for (i, word) in words.iter().enumerate() {
+            assert_eq!(counts[i], grid.count_word(word), "{}", word);
+        }
+
+        // Same matches as searching for one word at a time
+        let mut expected: std::vec::Vec<WordMatch> = grid.find_word("XMAS").collect();
+        let mut found = std::vec::Vec::new();
+        dictionary.for_each_match(&grid, |m| if m.word == 0 {
+            found.push(WordMatch { x: m.x, y: m.y, direction: m.direction });
+        });
+        expected.sort_by_key(|m| (m.x, m.y, m.direction as u8));
+        found.sort_by_key(|m| (m.x, m.y, m.direction as u8));
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_row_window() {
+        let mut window: RowWindow<4, 2> = RowWindow::new();
+        assert_eq!(window.last_row(), None);
+        window.push(b"ab").unwrap();
+        window.push(b"cd").unwrap();
+        window.push(b"ef").unwrap();
+        assert_eq!(window.push(b"ghijk"), Err(LineTooLong));
+        assert_eq!(window.last_row(), Some(2));
+        assert_eq!(window.get(0, 0), None);
+        assert_eq!(window.get(1, 1), Some(b'd'));
+        assert_eq!(window.get(0, 2), Some(b'e'));
+        assert_eq!(window.get(0, 3), None);
+        assert_eq!(window.get(2, 2), None);
+    }
+
+    #[test]
+    fn test_streaming() {
+        let x_mas = Template::<5>::parse(X_MAS).unwrap();
+        for text in [EXAMPLE_CONTENT, include_str!("../data/day4/input.txt")] {
+            let grid = Grid::parse(text).unwrap();
+
+            for word in ["XMAS", "MAS", "A", "XX"] {
+                let mut stream: WordStream<140, 4> = WordStream::new(word);
+                for line in text.lines() {
+                    stream.push_line(line.as_bytes()).unwrap();
+                }
+                assert_eq!(stream.count(), grid.count_word(word), "{}", word);
+            }
+
+            let mut stream: TemplateStream<140, 3, 5> = TemplateStream::new(&x_mas);
+            for line in text.lines() {
+                stream.push_line(line.as_bytes()).unwrap();
+            }
+            assert_eq!(stream.count(), grid.count_template(&x_mas));
+        }
+    }
+
+    #[test]
+    fn test_highlight() {
+        let grid = Grid::parse(EXAMPLE_CONTENT).unwrap();
+        let highlight: Highlight<4> = grid.highlight_word("XMAS");
+        assert_eq!(std::format!("{}", highlight), "\
+....XXMAS.
+.SAMXMS...
+...S..A...
+..A.A.MS.X
+XMASAMX.MM
+X.....XA.A
+S.S.S.S.SS
+.A.A.A.A.A
+..M.M.M.MM
+.X.X.XMASX
+");
+
+        let x_mas = Template::<5>::parse(X_MAS).unwrap();
+        let highlight: Highlight<4> = grid.highlight_template(&x_mas);
+        assert_eq!(std::format!("{}", highlight), "\
+.M.S......
+..A..MSMS.
+.M.S.MAA..
+..A.ASMSM.
+.M.S.M....
+..........
+S.S.S.S.S.
+.A.A.A.A..
+M.M.M.M.M.
+..........
+");
+
+        let centres: std::vec::Vec<(usize, usize)> = grid.find_template(&x_mas)
+            .map(|m| m.centre)
+            .collect();
+        assert_eq!(centres.len(), 9);
+        assert_eq!(centres[0], (2, 1));
+        assert!(centres.iter().all(|&(x, y)| grid.get(x, y) == Some(b'A')));
+    }
+
+    #[test]
+    fn test_template() {
+        let template = Template::<5>::parse(X_MAS).unwrap();
+        assert_eq!(template.variants().len(), 4);
+        assert!(Template::<4>::parse(X_MAS).is_none());
+
+        // Fully symmetric shapes only have the one variant
+        let plus = Template::<5>::parse(".A.\nAAA\n.A.").unwrap();
+        assert_eq!(plus.variants().len(), 1);
+
+        // An L shape has all eight
+        let l = Template::<4>::parse("A.\nA.\nAB").unwrap();
+        assert_eq!(l.variants().len(), 8);
+
+        // The const search finds the same matches, up to the grid edges
+        let grid = Grid::parse(EXAMPLE_CONTENT).unwrap();
+        let corner = Template::<2>::parse("X.\n.M\n..").unwrap();
+        assert_eq!(const_count_template(EXAMPLE_CONTENT, &X_MAS_TEMPLATE), 9);
+        assert_eq!(const_count_template(EXAMPLE_CONTENT, &l), grid.count_template(&l));
+        assert_eq!(const_count_template(EXAMPLE_CONTENT, &plus), grid.count_template(&plus));
+        assert_eq!(const_count_template(EXAMPLE_CONTENT, &corner), grid.count_template(&corner));
+    }
+
+    #[test]
+    fn test_example_answer() {
+        let grid = Grid::parse(EXAMPLE_CONTENT).unwrap();
+        assert_eq!(grid.count_template(&Template::<5>::parse(X_MAS).unwrap()), 9);
+        assert_eq!(solve_both(EXAMPLE_CONTENT), (18, 9));
+    }
+
+    #[test]
+    fn test_line_endings() {
+        for text in line_ending_variants(EXAMPLE_CONTENT) {
+            assert_eq!(const_solve(&text), (18, 9), "{:?}", text);
+        }
+
+        // Rows padded differently cannot be read at one stride
+        let ragged = EXAMPLE_CONTENT.replacen('\n', "  \n", 1);
+        assert_eq!(Grid::parse(&ragged).err(), Some(MisalignedRow { line: 2 }));
+        let ragged = EXAMPLE_CONTENT.replace("MAMMMXMMMM\n", "MAMMMXMMMM \t\n");
+        assert_eq!(Grid::parse(&ragged).err(), Some(MisalignedRow { line: 9 }));
+    }
+
+    #[test]
+    fn test_const_solve() {
+        assert_eq!(const_solve(EXAMPLE_CONTENT), (18, 9));
+    }
+}
@@ -1,62 +1,27 @@
-use heapless::Vec;
-use crate::util::{count_lines, count_line_len};
+use crate::day4::{Grid, Highlight, WordMatches, WordStream, INPUT_CONTENT, XMAS};
+use crate::util::grid::{count_lines, count_line_len};
 
-const INPUT_CONTENT: &str = include_str!("../data/day4/input.txt");
 const INPUT_NUM_LINES: usize = count_lines(INPUT_CONTENT);
 const INPUT_LINE_LEN: usize = count_line_len(INPUT_CONTENT);
-const GRID_SIZE: usize = INPUT_NUM_LINES * INPUT_LINE_LEN;
+const MASK_WORDS: usize = (INPUT_NUM_LINES * INPUT_LINE_LEN).div_ceil(32);
 
-type Grid = Vec<char, GRID_SIZE>;
-type Quad = [char; 4];
-
-fn parse_grid(text: &str) -> Grid {
-    let mut grid = Grid::new();
-    text.lines().for_each(|line| line.chars().for_each(|c| {
-        let _ = grid.push(c);
-    }));
-    grid
+pub fn answer() -> u32 {
+    Grid::parse(INPUT_CONTENT).unwrap().count_word(XMAS)
 }
 
-fn grid_get(grid: &Grid, x: usize, y: usize) -> Option<char> {
-    if x >= INPUT_LINE_LEN || y >= INPUT_NUM_LINES {
-        None
-    } else {
-        grid.get(y * INPUT_LINE_LEN + x).copied()
-    }
+pub fn matches() -> WordMatches<'static, 'static> {
+    Grid::parse(INPUT_CONTENT).unwrap().find_word(XMAS)
 }
 
-fn grid_get_4(grid: &Grid, x_start: usize, y_start: usize, dir_x: i8, dir_y: i8) -> Option<Quad> {
-    let mut quad: Quad = ['.'; 4];
-    for i in 0..4 {
-        let x =  x_start + (i * dir_x) as usize;
-        let y = y_start + (i * dir_y) as usize;
-        match grid_get(grid, x, y) {
-            Some(c) => quad[i as usize] = c,
-            None => return None
-        };
-    }
-    Some(quad)
+pub fn render() -> Highlight<'static, MASK_WORDS> {
+    Grid::parse(INPUT_CONTENT).unwrap().highlight_word(XMAS)
 }
 
-fn grid_find_xmas(grid: &Grid) -> u32 {
-    let xmas: [char; 4] = ['X', 'M', 'A', 'S'];
-    let directions = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, -1), (-1, 1)];
-    let mut total = 0;
-    for x in 0..INPUT_LINE_LEN {
-        for y in 0..INPUT_NUM_LINES {
-            for (dir_x, dir_y) in directions {
-                    match grid_get_4(grid, x, y, dir_x, dir_y) {
-                        Some(quad) => if quad == xmas {total += 1},
-                        None => continue
-                    }
-            }
-        }
+// Same answer holding only four rows of the grid at a time
+pub fn answer_streaming() -> u32 {
+    let mut stream: WordStream<INPUT_LINE_LEN, { XMAS.len() }> = WordStream::new(XMAS);
+    for line in INPUT_CONTENT.trim_end().lines() {
+        stream.push_line(line.trim_end().as_bytes()).unwrap();
     }
-    total
-}
-
-pub fn answer() -> u32 {
-    let grid = parse_grid(INPUT_CONTENT);
-    let answer = grid_find_xmas(&grid);
-    return answer;
+    stream.count()
 }
@@ -0,0 +1,519 @@
+use core::borrow::{Borrow, BorrowMut};
+use heapless::{Vec, FnvIndexSet};
+use nom::bytes::complete::tag;
+use nom::branch::alt;
+use nom::character::complete::{multispace0, space0};
+use nom::sequence::{preceded, separated_pair, terminated};
+use nom::combinator::{all_consuming, eof};
+use nom::error::{Error, ErrorKind};
+use nom::IResult;
+use crate::util::parse::{line_end, lines_heapless, separated_list1_heapless};
+use crate::util::scan::{digits, number, skip_blanks};
+
+pub const INPUT_CONTENT: &str = crate::inputs::DAY5;
+
+// Pages are two digit numbers so rules between them fit a bit matrix, any
+// rule with a larger page goes in a small fallback set instead
+pub const MATRIX_PAGES: usize = 100;
+pub const MATRIX_WORDS: usize = (MATRIX_PAGES * MATRIX_PAGES).div_ceil(32);
+const FALLBACK_LEN: usize = 64;
+
+pub const UPDATES_LIST_MAX_LEN: usize = count_updates(INPUT_CONTENT);
+pub const UPDATE_MAX_LEN: usize = 23;
+// Each pair of pages in an update can break at most one rule
+const VIOLATIONS_MAX_LEN: usize = UPDATE_MAX_LEN * (UPDATE_MAX_LEN - 1) / 2;
+
+// Lines starting with a page that are not rules
+const fn count_updates(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut count = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let starts_with_page = digits(bytes, skip_blanks(bytes, i)).is_some();
+        let mut rule = false;
+        while i < bytes.len() && bytes[i] != b'\n' {
+            rule |= bytes[i] == b'|';
+            i += 1;
+        }
+        count += (starts_with_page && !rule) as usize;
+        i += 1;
+    }
+    count
+}
+
+pub type Rule = (u8, u8);
+pub type Update = Vec<u8, UPDATE_MAX_LEN>;
+pub type UpdatesList = Vec<Update, UPDATES_LIST_MAX_LEN>;
+pub type Violations = Vec<Violation, VIOLATIONS_MAX_LEN>;
+
+#[derive(Debug, PartialEq)]
+pub enum OrderError {
+    // Every remaining page has a predecessor, so these pages form or sit
+    // behind a cycle
+    Cycle(Update),
+    // No rule, direct or implied, decides which of the two pages goes first
+    Ambiguous(u8, u8),
+    // There is no middle page of an empty update
+    Empty,
+    // More pages than an `Update` can hold
+    TooManyPages(usize),
+}
+
+// A rule whose pages appear the wrong way round. `positions` holds the indices
+// of `rule.0` and `rule.1` in the update, so the first is always the larger.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Violation {
+    pub rule: Rule,
+    pub positions: (usize, usize),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Report {
+    pub violations: Violations,
+    // Minimum number of adjacent swaps that puts the update in order, or why
+    // the rules do not give it one order
+    pub swaps: Result<usize, OrderError>,
+}
+
+pub type Matrix = [u32; MATRIX_WORDS];
+
+// Rule (a, b) is bit a * MATRIX_PAGES + b of the matrix. The matrix is owned
+// by default, or borrowed so a table kept in flash is read where it lies.
+pub struct RuleTable<M = Matrix> {
+    matrix: M,
+    fallback: FnvIndexSet<Rule, FALLBACK_LEN>,
+}
+
+impl RuleTable {
+    pub fn new() -> Self {
+        RuleTable {
+            matrix: [0; MATRIX_WORDS],
+            fallback: FnvIndexSet::new()
+        }
+    }
+}
+
+impl<'a> RuleTable<&'a Matrix> {
+    // Hands back the first fallback rule that did not fit
+    pub fn from_matrix(matrix: &'a Matrix, fallback: &[Rule]) -> Result<Self, Rule> {
+        let mut table = RuleTable { matrix, fallback: FnvIndexSet::new() };
+        for &rule in fallback {
+            table.fallback.insert(rule)?;
+        }
+        Ok(table)
+    }
+}
+
+impl<M: Borrow<Matrix>> RuleTable<M> {
+    fn bit(rule: Rule) -> Option<(usize, u32)> {
+        let (a, b) = (rule.0 as usize, rule.1 as usize);
+        if a < MATRIX_PAGES && b < MATRIX_PAGES {
+            let i = a * MATRIX_PAGES + b;
+            Some((i / 32, 1 << (i % 32)))
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, rule: Rule) -> bool {
+        match Self::bit(rule) {
+            Some((word, mask)) => self.matrix.borrow()[word] & mask != 0,
+            None => self.fallback.contains(&rule)
+        }
+    }
+}
+
+impl<M: BorrowMut<Matrix>> RuleTable<M> {
+    // Same contract as `IndexSet::insert`, the rule is handed back if the
+    // fallback set is full
+    pub fn insert(&mut self, rule: Rule) -> Result<bool, Rule> {
+        match Self::bit(rule) {
+            Some((word, mask)) => {
+                let word = &mut self.matrix.borrow_mut()[word];
+                let added = *word & mask == 0;
+                *word |= mask;
+                Ok(added)
+            }
+            None => self.fallback.insert(rule)
+        }
+    }
+
+    pub fn remove(&mut self, rule: Rule) -> bool {
+        match Self::bit(rule) {
+            Some((word, mask)) => {
+                let word = &mut self.matrix.borrow_mut()[word];
+                let removed = *word & mask != 0;
+                *word &= !mask;
+                removed
+            }
+            None => self.fallback.remove(&rule)
+        }
+    }
+}
+
+impl Default for RuleTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct PageOrdering<M = Matrix> {
+    rules: RuleTable<M>,
+}
+
+impl PageOrdering {
+    // Hands back the first rule that did not fit the table
+    pub fn new(rules: &[Rule]) -> Result<Self, Rule> {
+        let mut table = RuleTable::new();
+        for &rule in rules {
+            table.insert(rule)?;
+        }
+        Ok(PageOrdering { rules: table })
+    }
+
+    // Reads the rules section of the puzzle input along with the blank line
+    // that ends it, or up to the end of the input. Any other line is an error.
+    pub fn parse(mut input: &str) -> IResult<&str, Self> {
+        let mut ordering = PageOrdering::default();
+        loop {
+            let (rest, (a, b)) = match terminated(parse_pair, line_end)(input) {
+                Err(nom::Err::Error(_)) => break,
+                result => result?
+            };
+            if ordering.add_rule(a, b).is_err() {
+                return Err(nom::Err::Failure(Error::new(input, ErrorKind::TooLarge)));
+            }
+            input = rest;
+        }
+        let (input, _) = alt((line_end, preceded(space0, eof)))(input)?;
+        Ok((input, ordering))
+    }
+}
+
+impl<M> PageOrdering<M> {
+    pub fn from_table(rules: RuleTable<M>) -> Self {
+        PageOrdering { rules }
+    }
+}
+
+impl<M: BorrowMut<Matrix>> PageOrdering<M> {
+    pub fn add_rule(&mut self, a: u8, b: u8) -> Result<bool, Rule> {
+        self.rules.insert((a, b))
+    }
+
+    pub fn remove_rule(&mut self, a: u8, b: u8) -> bool {
+        self.rules.remove((a, b))
+    }
+}
+
+impl<M: Borrow<Matrix>> PageOrdering<M> {
+    pub fn precedes(&self, a: u8, b: u8) -> bool {
+        self.rules.contains((a, b))
+    }
+
+    // Checks every pair directly rather than sorting
+    pub fn is_valid(&self, pages: &[u8]) -> bool {
+        pages.iter()
+            .enumerate()
+            .all(|(i, &a)| pages[i + 1..].iter().all(|&b| !self.precedes(b, a)))
+    }
+
+    // Kahn's algorithm over the rules between the given pages only. The order
+    // must be unique, so exactly one page may be free at every step.
+    pub fn sort(&self, pages: &[u8]) -> Result<Update, OrderError> {
+        let n = pages.len();
+        if n > UPDATE_MAX_LEN {
+            return Err(OrderError::TooManyPages(n));
+        }
+        let mut in_degree: Vec<usize, UPDATE_MAX_LEN> = pages.iter()
+            .map(|&b| pages.iter().filter(|&&a| self.precedes(a, b)).count())
+            .collect();
+        let mut placed = [false; UPDATE_MAX_LEN];
+
+        let mut sorted = Update::new();
+        while sorted.len() < n {
+            let mut free = (0..n).filter(|&i| !placed[i] && in_degree[i] == 0);
+            let i = match free.next() {
+                Some(i) => i,
+                None => {
+                    let remaining = (0..n).filter(|&i| !placed[i]).map(|i| pages[i]).collect();
+                    return Err(OrderError::Cycle(remaining));
+                }
+            };
+            if let Some(j) = free.next() {
+                return Err(OrderError::Ambiguous(pages[i], pages[j]));
+            }
+
+            placed[i] = true;
+            let _ = sorted.push(pages[i]);
+            for j in 0..n {
+                if !placed[j] && self.precedes(pages[i], pages[j]) {
+                    in_degree[j] -= 1;
+                }
+            }
+        }
+
+        Ok(sorted)
+    }
+
+    // Middle page once the update is in order
+    pub fn middle(&self, pages: &[u8]) -> Result<u8, OrderError> {
+        if pages.is_empty() {
+            Err(OrderError::Empty)
+        } else if self.is_valid(pages) {
+            Ok(middle_page(pages))
+        } else {
+            self.sort(pages).map(|sorted| middle_page(&sorted))
+        }
+    }
+
+    pub fn violations(&self, pages: &[u8]) -> Result<Violations, OrderError> {
+        let n = pages.len();
+        if n > UPDATE_MAX_LEN {
+            return Err(OrderError::TooManyPages(n));
+        }
+        let mut violations = Violations::new();
+        for (i, &a) in pages.iter().enumerate() {
+            for (j, &b) in pages.iter().enumerate().skip(i + 1) {
+                if self.precedes(b, a) {
+                    violations.push(Violation { rule: (b, a), positions: (j, i) })
+                        .map_err(|_| OrderError::TooManyPages(n))?;
+                }
+            }
+        }
+        Ok(violations)
+    }
+
+    // Every pair out of place in the sorted order costs one adjacent swap,
+    // which also covers pairs only ordered through other pages
+    fn swaps(&self, pages: &[u8]) -> Result<usize, OrderError> {
+        let sorted = self.sort(pages)?;
+        let rank = |page| sorted.iter().position(|&p| p == page).unwrap();
+        let mut swaps = 0;
+        for (i, &a) in pages.iter().enumerate() {
+            swaps += pages[i + 1..].iter().filter(|&&b| rank(b) < rank(a)).count();
+        }
+        Ok(swaps)
+    }
+
+    // The violations are there even when the rules leave the pages only
+    // partly ordered, in which case `swaps` holds the sort error
+    pub fn report(&self, pages: &[u8]) -> Result<Report, OrderError> {
+        let violations = self.violations(pages)?;
+        Ok(Report { violations, swaps: self.swaps(pages) })
+    }
+
+    pub fn reports<'a>(&'a self, updates: &'a [Update]) -> impl Iterator<Item = Result<Report, OrderError>> + 'a {
+        updates.iter().map(|update| self.report(update))
+    }
+}
+
+impl Default for PageOrdering {
+    fn default() -> Self {
+        Self::from_table(RuleTable::new())
+    }
+}
+
+// Fails unless every line up to the end of the input is a rule, the blank
+// line after them, or an update
+pub fn parse(input: &str) -> IResult<&str, (PageOrdering, UpdatesList)> {
+    let (input, ordering) = PageOrdering::parse(input)?;
+    let (input, updates) = parse_all_updates(input)?;
+    let (input, _) = all_consuming(multispace0)(input)?;
+    Ok((input, (ordering, updates)))
+}
+
+pub fn parse_pair(input: &str) -> IResult<&str, Rule> {
+    separated_pair(number, tag("|"), number)(input)
+}
+
+pub fn parse_update(input: &str) -> IResult<&str, Update> {
+    separated_list1_heapless(tag(","), number)(input)
+}
+
+pub fn parse_all_updates(input: &str) -> IResult<&str, UpdatesList> {
+    lines_heapless(parse_update)(input)
+}
+
+fn middle_page(update: &[u8]) -> u8 {
+    update[update.len() / 2]
+}
+
+// Shares one parse, and checks each update once to decide which part it
+// belongs to
+pub fn solve_both(input: &str) -> (u32, u32) {
+    let (_, (ordering, updates)) = parse(input).unwrap();
+    sum_middles(&ordering, updates.iter().map(|update| update.as_slice()))
+}
+
+#[cfg(feature = "preparsed")]
+pub fn solve_preparsed() -> (u32, u32) {
+    use crate::preparsed::day5::{FALLBACK, PAGES, RULES, UPDATES};
+    let ordering = PageOrdering::from_table(RuleTable::from_matrix(&RULES, &FALLBACK).unwrap());
+    let updates = UPDATES.iter().map(|&(start, len)| &PAGES[start..][..len]);
+    sum_middles(&ordering, updates)
+}
+
+fn sum_middles<'u, M: Borrow<Matrix>>(ordering: &PageOrdering<M>, updates: impl Iterator<Item = &'u [u8]>) -> (u32, u32) {
+    let (mut valid, mut fixed) = (0, 0);
+    for update in updates {
+        if ordering.is_valid(update) {
+            valid += middle_page(update) as u32;
+        } else {
+            fixed += middle_page(&ordering.sort(update).unwrap()) as u32;
+        }
+    }
+    (valid, fixed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_CONTENT: &str = include_str!("../data/day5/example.txt");
+
+    const EXAMPLE_RULES: [Rule; 21] = [
+        (47, 53), (97, 13), (97, 61), (97, 47), (75, 29), (61, 13), (75, 53),
+        (29, 13), (97, 29), (53, 29), (61, 53), (97, 53), (61, 29), (47, 13),
+        (75, 47), (97, 75), (47, 61), (75, 61), (47, 29), (75, 13), (53, 13),
+    ];
+
+    #[test]
+    fn test_sort() {
+        let ordering = PageOrdering::new(&EXAMPLE_RULES).unwrap();
+        assert_eq!(ordering.sort(&[75, 47, 61, 53, 29]).unwrap(), [75, 47, 61, 53, 29]);
+        assert_eq!(ordering.sort(&[75, 97, 47, 61, 53]).unwrap(), [97, 75, 47, 61, 53]);
+        assert_eq!(ordering.sort(&[61, 13, 29]).unwrap(), [61, 29, 13]);
+        assert_eq!(ordering.sort(&[97, 13, 75, 29, 47]).unwrap(), [97, 75, 47, 29, 13]);
+    }
+
+    #[test]
+    fn test_rule_table() {
+        let mut table = RuleTable::new();
+        assert_eq!(table.insert((0, 99)), Ok(true));
+        assert_eq!(table.insert((0, 99)), Ok(false));
+        assert_eq!(table.insert((99, 150)), Ok(true));
+        assert!(table.contains((0, 99)));
+        assert!(table.contains((99, 150)));
+        assert!(!table.contains((99, 0)));
+        assert!(!table.contains((150, 99)));
+
+        let rules: std::vec::Vec<Rule> = (100..=200).map(|b| (0, b)).collect();
+        assert_eq!(PageOrdering::new(&rules).err(), Some((0, 164)));
+
+        let matrix = [0; MATRIX_WORDS];
+        let table = RuleTable::from_matrix(&matrix, &rules[..FALLBACK_LEN]).unwrap();
+        assert!(table.contains((0, 163)));
+        assert!(!table.contains((0, 164)));
+        assert_eq!(RuleTable::from_matrix(&matrix, &rules).err(), Some((0, 164)));
+    }
+
+    #[test]
+    fn test_is_valid() {
+        let ordering = PageOrdering::new(&EXAMPLE_RULES).unwrap();
+        assert!(ordering.is_valid(&[75, 47, 61, 53, 29]));
+        assert!(ordering.is_valid(&[97, 61, 53, 29, 13]));
+        assert!(!ordering.is_valid(&[75, 97, 47, 61, 53]));
+        assert!(!ordering.is_valid(&[61, 13, 29]));
+    }
+
+    #[test]
+    fn test_report() {
+        let ordering = PageOrdering::new(&EXAMPLE_RULES).unwrap();
+        let report = ordering.report(&[75, 47, 61, 53, 29]).unwrap();
+        assert!(report.violations.is_empty());
+        assert_eq!(report.swaps, Ok(0));
+
+        let report = ordering.report(&[75, 97, 47, 61, 53]).unwrap();
+        assert_eq!(report.violations, [Violation { rule: (97, 75), positions: (1, 0) }]);
+        assert_eq!(report.swaps, Ok(1));
+
+        let report = ordering.report(&[97, 13, 75, 29, 47]).unwrap();
+        assert_eq!(report.violations, [
+            Violation { rule: (75, 13), positions: (2, 1) },
+            Violation { rule: (29, 13), positions: (3, 1) },
+            Violation { rule: (47, 13), positions: (4, 1) },
+            Violation { rule: (47, 29), positions: (4, 3) },
+        ]);
+        assert_eq!(report.swaps, Ok(4));
+    }
+
+    #[test]
+    fn test_report_implied_order() {
+        // 3 must come before 1 through 2, but there is no rule to break directly
+        let ordering = PageOrdering::new(&[(3, 2), (2, 1)]).unwrap();
+        let report = ordering.report(&[1, 2, 3]).unwrap();
+        assert_eq!(report.violations.len(), 2);
+        assert_eq!(report.swaps, Ok(3));
+    }
+
+    #[test]
+    fn test_parse() {
+        let (rest, (ordering, updates)) = parse(EXAMPLE_CONTENT).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(updates.len(), 6);
+        assert_eq!(count_updates(EXAMPLE_CONTENT), 6);
+        assert_eq!(updates[5], [97, 13, 75, 29, 47]);
+        for (a, b) in EXAMPLE_RULES {
+            assert!(ordering.precedes(a, b));
+            assert!(!ordering.precedes(b, a));
+        }
+
+        assert!(parse("47|53\n\n75,47\n").is_ok());
+        assert!(parse("47|53\n47|x\n\n75,47\n").is_err());
+        assert!(parse("47|53\n\n75,47\n75;47\n").is_err());
+        assert!(parse("47|53\n\n75,47\n\n97,13\n").is_err());
+        assert_eq!(PageOrdering::parse("47|53\n").map(|(rest, _)| rest), Ok(""));
+        assert!(PageOrdering::parse("47|53\n53-29\n").is_err());
+    }
+
+    #[test]
+    fn test_incremental_rules() {
+        let mut ordering = PageOrdering::default();
+        assert!(ordering.is_valid(&[2, 1]));
+        assert_eq!(ordering.add_rule(1, 2), Ok(true));
+        assert!(!ordering.is_valid(&[2, 1]));
+        assert_eq!(ordering.sort(&[2, 1]).unwrap(), [1, 2]);
+        assert!(ordering.remove_rule(1, 2));
+        assert!(!ordering.remove_rule(1, 2));
+        assert!(ordering.is_valid(&[2, 1]));
+    }
+
+    #[test]
+    fn test_middle() {
+        let ordering = PageOrdering::new(&EXAMPLE_RULES).unwrap();
+        assert_eq!(ordering.middle(&[75, 47, 61, 53, 29]), Ok(61));
+        assert_eq!(ordering.middle(&[61, 13, 29]), Ok(29));
+        assert_eq!(ordering.middle(&[]), Err(OrderError::Empty));
+    }
+
+    #[test]
+    fn test_example_answer() {
+        assert_eq!(solve_both(EXAMPLE_CONTENT), (143, 123));
+    }
+
+    #[test]
+    fn test_cycle() {
+        let ordering = PageOrdering::new(&[(1, 2), (2, 3), (3, 1), (4, 1)]).unwrap();
+        let expected = Update::from_slice(&[1, 2, 3]).unwrap();
+        assert_eq!(ordering.sort(&[4, 1, 2, 3]), Err(OrderError::Cycle(expected)));
+    }
+
+    #[test]
+    fn test_ambiguous() {
+        let ordering = PageOrdering::new(&[(1, 2), (1, 3)]).unwrap();
+        assert_eq!(ordering.sort(&[3, 2, 1]), Err(OrderError::Ambiguous(3, 2)));
+        assert_eq!(ordering.sort(&[3, 1]).unwrap(), [1, 3]);
+        let pages: std::vec::Vec<u8> = (0..30).collect();
+        assert_eq!(ordering.sort(&pages), Err(OrderError::TooManyPages(30)));
+        assert_eq!(ordering.report(&pages), Err(OrderError::TooManyPages(30)));
+
+        // Pages the rules only partly order still have their violations
+        let report = ordering.report(&[3, 2, 1]).unwrap();
+        assert_eq!(report.violations, [
+            Violation { rule: (1, 3), positions: (2, 0) },
+            Violation { rule: (1, 2), positions: (2, 1) },
+        ]);
+        assert_eq!(report.swaps, Err(OrderError::Ambiguous(3, 2)));
+    }
+}
@@ -0,0 +1,273 @@
+use heapless::Vec;
+use crate::util::grid::{count_lines, count_line_len, Grid, MisalignedRow};
+
+pub const INPUT_CONTENT: &str = crate::inputs::DAY6;
+const GRID_Y_MAX: usize = count_lines(INPUT_CONTENT);
+const GRID_X_MAX: usize = count_line_len(INPUT_CONTENT);
+const GRID_SIZE: usize = GRID_Y_MAX * GRID_X_MAX;
+const SEEN_WORDS: usize = GRID_SIZE.div_ceil(32);
+// Four bits per cell, one for each direction the guard can turn in
+const TURN_WORDS: usize = GRID_SIZE.div_ceil(8);
+
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    North, East, South, West
+}
+
+impl Direction {
+    fn turn_right(self) -> Self {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+pub type Point = (usize, usize);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LabError {
+    Misaligned(MisalignedRow),
+    // More cells than the puzzle input, which sizes the patrol and scratch space
+    TooLarge,
+    NoGuard,
+}
+
+impl From<MisalignedRow> for LabError {
+    fn from(e: MisalignedRow) -> Self {
+        LabError::Misaligned(e)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Guard {
+    pub position: Point,
+    pub direction: Direction,
+}
+
+// A guard packed small enough to keep one for every cell of the patrol
+#[derive(Copy, Clone, Debug)]
+struct Visit {
+    index: u16,
+    direction: Direction,
+}
+
+// The cells the guard walks onto in order. Each is kept as the guard just
+// before stepping onto it for the first time, the start is kept as itself.
+pub struct Patrol {
+    visits: Vec<Visit, GRID_SIZE>,
+}
+
+impl Patrol {
+    // Distinct positions visited, including the start
+    pub fn len(&self) -> usize {
+        self.visits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.visits.is_empty()
+    }
+}
+
+// The lab floor as read from the puzzle text, plus at most one extra
+// obstruction laid over it
+#[derive(Copy, Clone)]
+pub struct Lab<'a> {
+    floor: Grid<'a>,
+    start: Guard,
+    extra: Option<Point>,
+}
+
+impl<'a> Lab<'a> {
+    pub fn parse(text: &'a str) -> Result<Self, LabError> {
+        Self::new(Grid::parse(text)?)
+    }
+
+    pub fn from_cells(cells: &'a [u8], width: usize) -> Result<Self, LabError> {
+        Self::new(Grid::from_cells(cells, width))
+    }
+
+    fn new(floor: Grid<'a>) -> Result<Self, LabError> {
+        if floor.width() * floor.height() > GRID_SIZE {
+            return Err(LabError::TooLarge);
+        }
+        let position = floor.find(b'^').ok_or(LabError::NoGuard)?;
+        let start = Guard { position, direction: Direction::North };
+
+        Ok(Lab { floor, start, extra: None })
+    }
+
+    pub fn start(&self) -> Guard {
+        self.start
+    }
+
+    pub fn with_obstruction(self, p: Point) -> Self {
+        Lab { extra: Some(p), ..self }
+    }
+
+    fn is_obstruction(&self, p: Point) -> bool {
+        let (x, y) = p;
+        self.extra == Some(p) || self.floor.get(x, y) == Some(b'#')
+    }
+
+    fn index(&self, p: Point) -> usize {
+        let (x, y) = p;
+        x + y * self.floor.width()
+    }
+
+    fn pack(&self, guard: Guard) -> Visit {
+        Visit { index: self.index(guard.position) as u16, direction: guard.direction }
+    }
+
+    fn unpack(&self, visit: Visit) -> Guard {
+        let i = visit.index as usize;
+        let width = self.floor.width();
+        Guard { position: (i % width, i / width), direction: visit.direction }
+    }
+
+    fn ahead(&self, guard: Guard) -> Option<Point> {
+        let (x, y) = guard.position;
+        match guard.direction {
+            Direction::North => Some((x, y.checked_sub(1)?)),
+            Direction::East => (x + 1 < self.floor.width()).then_some((x + 1, y)),
+            Direction::South => (y + 1 < self.floor.height()).then_some((x, y + 1)),
+            Direction::West => Some((x.checked_sub(1)?, y))
+        }
+    }
+
+    // Turns or moves the guard once, None when they walk off the map
+    pub fn step(&self, guard: Guard) -> Option<Guard> {
+        let ahead = self.ahead(guard)?;
+        if self.is_obstruction(ahead) {
+            Some(Guard { direction: guard.direction.turn_right(), ..guard })
+        } else {
+            Some(Guard { position: ahead, ..guard })
+        }
+    }
+
+    pub fn patrol(&self) -> Patrol {
+        let mut seen = [0u32; SEEN_WORDS];
+        let mut mark = |p: Point| {
+            let i = self.index(p);
+            let new = seen[i / 32] & (1 << (i % 32)) == 0;
+            seen[i / 32] |= 1 << (i % 32);
+            new
+        };
+
+        let mut visits = Vec::new();
+        let mut guard = self.start;
+        mark(guard.position);
+        let _ = visits.push(self.pack(guard));
+        while let Some(next) = self.step(guard) {
+            if next.position != guard.position && mark(next.position) {
+                // Never full, the lab has at most GRID_SIZE cells to visit
+                let _ = visits.push(self.pack(guard));
+            }
+            guard = next;
+        }
+
+        Patrol { visits }
+    }
+
+    // A loop has to repeat a turn, so only turns are recorded. `turns` is
+    // scratch space the caller can reuse between walks.
+    fn loops_from(&self, mut guard: Guard, turns: &mut [u32; TURN_WORDS]) -> bool {
+        turns.fill(0);
+        while let Some(next) = self.step(guard) {
+            if next.direction != guard.direction {
+                let i = self.index(guard.position);
+                let bit = (guard.direction.bit() as u32) << (4 * (i % 8));
+                let word = &mut turns[i / 8];
+                if *word & bit != 0 {
+                    return true;
+                }
+                *word |= bit;
+            }
+            guard = next;
+        }
+        false
+    }
+
+    // An obstruction can only change the patrol if it sits on it. Placed on
+    // a cell, the patrol is unchanged up to the first time the guard would
+    // have stepped there, so each walk resumes from that point.
+    pub fn loop_obstructions(&self, patrol: &Patrol) -> usize {
+        let mut turns = [0; TURN_WORDS];
+        patrol.visits.iter()
+            .skip(1)
+            .filter(|&&visit| {
+                let guard = self.unpack(visit);
+                let cell = self.ahead(guard).unwrap();
+                self.with_obstruction(cell).loops_from(guard, &mut turns)
+            })
+            .count()
+    }
+}
+
+// Part b resumes from the states part a records
+pub fn solve_both(input: &str) -> (usize, usize) {
+    let lab = Lab::parse(input).unwrap();
+    let patrol = lab.patrol();
+    (patrol.len(), lab.loop_obstructions(&patrol))
+}
+
+#[cfg(feature = "preparsed")]
+pub fn solve_preparsed() -> (usize, usize) {
+    use crate::preparsed::day6::{GRID, WIDTH};
+    let lab = Lab::from_cells(&GRID, WIDTH).unwrap();
+    let patrol = lab.patrol();
+    (patrol.len(), lab.loop_obstructions(&patrol))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_CONTENT: &str = include_str!("../data/day6/example.txt");
+
+    #[test]
+    fn test_example_answer() {
+        let lab = Lab::parse(EXAMPLE_CONTENT).unwrap();
+        assert_eq!(lab.start(), Guard { position: (4, 6), direction: Direction::North });
+        assert_eq!(lab.patrol().len(), 41);
+        assert_eq!(solve_both(EXAMPLE_CONTENT), (41, 6));
+    }
+
+    #[test]
+    fn test_misaligned_rows() {
+        // Rows padded differently cannot be read at one stride
+        let ragged = EXAMPLE_CONTENT.replacen('\n', " \n", 1);
+        assert_eq!(Lab::parse(&ragged).err(), Some(LabError::Misaligned(MisalignedRow { line: 2 })));
+        let ragged = EXAMPLE_CONTENT.replacen(".#..^.....\n", ".#..^.....  \n", 1);
+        assert_eq!(Lab::parse(&ragged).err(), Some(LabError::Misaligned(MisalignedRow { line: 7 })));
+    }
+
+    #[test]
+    fn test_lab_errors() {
+        assert_eq!(Lab::parse("..\n..\n").err(), Some(LabError::NoGuard));
+        assert_eq!(Lab::parse("").err(), Some(LabError::NoGuard));
+        assert_eq!(Lab::from_cells(b"..^", 0).err(), Some(LabError::NoGuard));
+        // A guard in the cells left over after the last full row
+        assert_eq!(Lab::from_cells(b"....^", 2).err(), Some(LabError::NoGuard));
+        assert_eq!(Lab::from_cells(b".^..", 2).map(|lab| lab.start().position), Ok((1, 0)));
+
+        let wide = [b'.'; GRID_SIZE + 1];
+        assert_eq!(Lab::from_cells(&wide, GRID_SIZE + 1).err(), Some(LabError::TooLarge));
+    }
+
+    #[test]
+    fn test_obstruction() {
+        let mut turns = [0; TURN_WORDS];
+        let lab = Lab::parse(EXAMPLE_CONTENT).unwrap().with_obstruction((3, 6));
+        assert!(lab.loops_from(lab.start(), &mut turns));
+        let lab = Lab::parse(EXAMPLE_CONTENT).unwrap().with_obstruction((0, 0));
+        assert!(!lab.loops_from(lab.start(), &mut turns));
+    }
+}
@@ -0,0 +1,142 @@
+use heapless::Vec;
+use crate::util::combinatorics::CartesianPower;
+use nom::bytes::complete::tag;
+use nom::character::complete::multispace0;
+use nom::combinator::all_consuming;
+use nom::sequence::terminated;
+use nom::IResult;
+use crate::util::parse::{lines_heapless, separated_list1_heapless};
+use crate::util::grid::count_lines;
+use crate::util::scan::number;
+
+pub const INPUT_CONTENT: &str = crate::inputs::DAY7;
+
+// One equation per line
+const EQUATIONS_LIST_MAX_LEN: usize = count_lines(INPUT_CONTENT);
+const OPERANDS_MAX_LEN: usize = 12;
+pub const OPERATORS_MAX_LEN: usize = OPERANDS_MAX_LEN - 1;
+
+#[derive(Copy, Clone, Debug)]
+pub enum Op {
+    Add,
+    Mult,
+    Concat
+}
+
+pub type Operands = Vec<u64, OPERANDS_MAX_LEN>;
+pub type Equation = (u64, Operands);
+pub type EquationList = Vec<Equation, EQUATIONS_LIST_MAX_LEN>;
+pub type OpsPermutations<'a> = CartesianPower<'a, Op, OPERATORS_MAX_LEN>;
+
+// Fails unless every line up to the end of the input is an equation
+pub fn parse(input: &str) -> IResult<&str, EquationList> {
+    let (input, equations) = lines_heapless(parse_equation)(input)?;
+    let (input, _) = all_consuming(multispace0)(input)?;
+    Ok((input, equations))
+}
+
+fn parse_operands(input: &str) -> IResult<&str, Operands> {
+    separated_list1_heapless(tag(" "), number)(input)
+}
+
+fn parse_equation(input: &str) -> IResult<&str, Equation> {
+    let (input, result) = terminated(number, tag(": "))(input)?;
+    let (input, operands) = parse_operands(input)?;
+    Ok((input, (result, operands)))
+}
+
+fn concat_ints(mut a: u64, b: u64) -> u64 {
+    let mut temp = b;
+    while temp > 0 {
+        temp /= 10;
+        a *= 10;
+    }
+    a + b
+}
+
+fn evaluate_op(op: Op, a: u64, b: u64) -> u64 {
+    match op {
+        Op::Add => a + b,
+        Op::Mult => a * b,
+        Op::Concat => concat_ints(a, b)
+    }
+}
+
+// The test value if some choice of `ops` between the operands produces it
+pub fn check_equation(eq: &Equation, ops: &[Op]) -> Option<u64> {
+    let (expected, operands) = eq;
+    check_operands(*expected, operands, ops)
+}
+
+pub fn check_operands(expected: u64, operands: &[u64], ops: &[Op]) -> Option<u64> {
+    let iter = OpsPermutations::new(ops, operands.len() - 1);
+
+    for operators in iter {
+        // Compute current permutation
+        let mut i = 0;
+        let result  = operands.iter().copied().reduce(|acc, x| {
+            let val = evaluate_op(operators[i], acc, x);
+            i += 1;
+            val
+        }).unwrap();
+
+        // Check result
+        if result == expected {
+            return Some(result);
+        }
+    }
+
+    None
+}
+
+pub fn calibration(equations: &[Equation], ops: &[Op]) -> u64 {
+    equations.iter().filter_map(|eq| check_equation(eq, ops)).sum()
+}
+
+// Any equation part a can solve part b can too, so only the rest are
+// searched again with concatenation added
+pub fn solve_both(input: &str) -> (u64, u64) {
+    let (_, equations) = parse(input).unwrap();
+    sum_calibrations(equations.iter().map(|(expected, operands)| (*expected, operands.as_slice())))
+}
+
+#[cfg(feature = "preparsed")]
+pub fn solve_preparsed() -> (u64, u64) {
+    use crate::preparsed::day7::{EQUATIONS, OPERANDS};
+    let equations = EQUATIONS.iter()
+        .map(|&(expected, start, len)| (expected, &OPERANDS[start..][..len]));
+    sum_calibrations(equations)
+}
+
+fn sum_calibrations<'e>(equations: impl Iterator<Item = (u64, &'e [u64])>) -> (u64, u64) {
+    let (mut answer_a, mut answer_b) = (0, 0);
+    for (expected, operands) in equations {
+        if let Some(val) = check_operands(expected, operands, &[Op::Add, Op::Mult]) {
+            answer_a += val;
+            answer_b += val;
+        } else if let Some(val) = check_operands(expected, operands, &[Op::Add, Op::Mult, Op::Concat]) {
+            answer_b += val;
+        }
+    }
+    (answer_a, answer_b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_CONTENT: &str = include_str!("../data/day7/example.txt");
+
+    #[test]
+    fn test_example_answer() {
+        assert_eq!(solve_both(EXAMPLE_CONTENT), (3749, 11387));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("190: 10 19\n3267: 81 40 27\n\n").map(|(_, eqs)| eqs.len()), Ok(2));
+        assert!(parse("190: 10 19\n3267 81 40 27\n").is_err());
+        assert!(parse("190: 10 19\n\n3267: 81 40 27\n").is_err());
+        assert!(parse("190: 10 19,\n").is_err());
+    }
+}
@@ -0,0 +1,2 @@
+// Puzzle text picked by build.rs, either the example or the full input
+include!(concat!(env!("OUT_DIR"), "/inputs.rs"));
@@ -1,19 +1,52 @@
 #![cfg_attr(not(test), no_std)]
 
 
+pub mod answer;
+pub mod inputs;
+#[cfg(feature = "preparsed")]
+pub mod preparsed;
+pub mod registry;
 pub mod util;
 
+#[cfg(feature = "day1")]
+pub mod day1;
+#[cfg(feature = "day1")]
 pub mod day1a;
+#[cfg(feature = "day1")]
 pub mod day1b;
+#[cfg(feature = "day2")]
+pub mod day2;
+#[cfg(feature = "day2")]
 pub mod day2a;
+#[cfg(feature = "day2")]
 pub mod day2b;
+#[cfg(feature = "day3")]
+pub mod day3;
+#[cfg(feature = "day3")]
 pub mod day3a;
+#[cfg(feature = "day3")]
 pub mod day3b;
+#[cfg(feature = "day4")]
+pub mod day4;
+#[cfg(feature = "day4")]
 pub mod day4a;
+#[cfg(feature = "day4")]
 pub mod day4b;
+#[cfg(feature = "day5")]
+pub mod day5;
+#[cfg(feature = "day5")]
 pub mod day5a;
+#[cfg(feature = "day5")]
 pub mod day5b;
+#[cfg(feature = "day6")]
+pub mod day6;
+#[cfg(feature = "day6")]
 pub mod day6a;
+#[cfg(feature = "day6")]
 pub mod day6b;
+#[cfg(feature = "day7")]
+pub mod day7;
+#[cfg(feature = "day7")]
 pub mod day7a;
+#[cfg(feature = "day7")]
 pub mod day7b;
\ No newline at end of file
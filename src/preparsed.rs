@@ -0,0 +1,2 @@
+// Static tables build.rs parsed from the same inputs as `inputs`
+include!(concat!(env!("OUT_DIR"), "/preparsed.rs"));
@@ -0,0 +1,163 @@
+use crate::answer::Answer;
+
+// One entry per day, solving both parts from the given input
+pub struct Solver {
+    pub day: u8,
+    pub input: &'static str,
+    pub solve: fn(&str) -> (Answer, Answer),
+    // Reads the tables build.rs parsed, if the day has them
+    pub preparsed: Option<fn() -> (Answer, Answer)>,
+    // Hands back the answers worked out at compile time, if the day has them
+    pub answers: Option<fn() -> (Answer, Answer)>,
+}
+
+impl Solver {
+    pub fn run(&self) -> (Answer, Answer) {
+        match (self.answers, self.preparsed) {
+            (Some(answers), _) => answers(),
+            (None, Some(solve)) => solve(),
+            (None, None) => (self.solve)(self.input)
+        }
+    }
+}
+
+// Unused when no enabled day has preparsed tables
+#[allow(unused_macros)]
+#[cfg(feature = "preparsed")]
+macro_rules! preparsed {
+    ($day:ident) => { Some(|| both(crate::$day::solve_preparsed())) };
+}
+
+#[allow(unused_macros)]
+#[cfg(not(feature = "preparsed"))]
+macro_rules! preparsed {
+    ($day:ident) => { None };
+}
+
+// Unused when every day with a solution is disabled
+#[allow(dead_code)]
+fn both<A: Into<Answer>, B: Into<Answer>>((a, b): (A, B)) -> (Answer, Answer) {
+    (a.into(), b.into())
+}
+
+pub const SOLVERS: &[Solver] = &[
+    #[cfg(feature = "day1")]
+    Solver {
+        day: 1,
+        input: crate::day1::INPUT_CONTENT,
+        solve: |input| both(crate::day1::solve_both(input)),
+        preparsed: preparsed!(day1),
+        answers: Some(|| both(crate::day1::ANSWERS))
+    },
+    #[cfg(feature = "day2")]
+    Solver {
+        day: 2,
+        input: crate::day2::INPUT_CONTENT,
+        solve: |input| both(crate::day2::solve_both(input)),
+        preparsed: None,
+        answers: Some(|| both(crate::day2::ANSWERS))
+    },
+    #[cfg(feature = "day3")]
+    Solver {
+        day: 3,
+        input: crate::day3::INPUT_CONTENT,
+        solve: |input| both(crate::day3::solve_both(input)),
+        preparsed: None,
+        answers: Some(|| both(crate::day3::ANSWERS))
+    },
+    #[cfg(feature = "day4")]
+    Solver {
+        day: 4,
+        input: crate::day4::INPUT_CONTENT,
+        solve: |input| both(crate::day4::solve_both(input)),
+        preparsed: preparsed!(day4),
+        answers: Some(|| both(crate::day4::ANSWERS))
+    },
+    #[cfg(feature = "day5")]
+    Solver {
+        day: 5,
+        input: crate::day5::INPUT_CONTENT,
+        solve: |input| both(crate::day5::solve_both(input)),
+        preparsed: preparsed!(day5),
+        answers: None
+    },
+    #[cfg(feature = "day6")]
+    Solver {
+        day: 6,
+        input: crate::day6::INPUT_CONTENT,
+        solve: |input| both(crate::day6::solve_both(input)),
+        preparsed: preparsed!(day6),
+        answers: None
+    },
+    #[cfg(feature = "day7")]
+    Solver {
+        day: 7,
+        input: crate::day7::INPUT_CONTENT,
+        solve: |input| both(crate::day7::solve_both(input)),
+        preparsed: preparsed!(day7),
+        answers: None
+    },
+];
+
+pub fn solver(day: u8) -> Option<&'static Solver> {
+    SOLVERS.iter().find(|s| s.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::line_ending_variants;
+
+    const EXAMPLES: [&str; 7] = [
+        include_str!("../data/day1/example.txt"),
+        include_str!("../data/day2/example.txt"),
+        include_str!("../data/day3/example.txt"),
+        include_str!("../data/day4/example.txt"),
+        include_str!("../data/day5/example.txt"),
+        include_str!("../data/day6/example.txt"),
+        include_str!("../data/day7/example.txt"),
+    ];
+
+    #[test]
+    fn test_registry() {
+        assert!(SOLVERS.windows(2).all(|w| w[0].day < w[1].day));
+        assert!(solver(26).is_none());
+    }
+
+    #[cfg(feature = "day1")]
+    #[test]
+    fn test_day1() {
+        let day1 = solver(1).unwrap();
+        let (a, b) = (day1.solve)(EXAMPLES[0]);
+        assert_eq!(a, 11u32);
+        assert_eq!(b, 31usize);
+    }
+
+    // However a day gets its answers, they must be the ones solving its input
+    // gives
+    #[test]
+    fn test_run() {
+        for solver in SOLVERS {
+            let expected = (solver.solve)(solver.input);
+            assert_eq!(solver.run(), expected, "day {}", solver.day);
+            if let Some(answers) = solver.answers {
+                assert_eq!(answers(), expected, "day {} answers", solver.day);
+            }
+            if let Some(preparsed) = solver.preparsed {
+                assert_eq!(preparsed(), expected, "day {} preparsed", solver.day);
+            }
+        }
+    }
+
+    // Checked on the examples, which solve quickly whatever input is embedded
+    #[test]
+    fn test_line_endings() {
+        for solver in SOLVERS {
+            let example = EXAMPLES[solver.day as usize - 1];
+            let expected = (solver.solve)(example);
+            for text in line_ending_variants(example) {
+                assert_eq!((solver.solve)(&text), expected, "day {} {:?}", solver.day, text);
+            }
+        }
+    }
+}
@@ -1,45 +1,16 @@
-pub const fn count_lines(text: &str) -> usize {
-    let len = text.len();
-    if len == 0 {
-        return 0;
-    }
+pub mod aho_corasick;
+pub mod combinatorics;
+pub mod grid;
+pub mod parse;
+pub mod scan;
 
-    let bytes = text.as_bytes();
-    let mut count = 1;
-    let mut i = 0;
-    let mut i_last_nl = 0;
-    while i < len {
-        let b = bytes[i];
-        if b == b'\n' {
-            count += 1;
-            i_last_nl = i;
-        }
-        i += 1;
-    }
-
-    // Account for an optional trailing newline
-    if i_last_nl == len - 1 {
-        count -= 1;
-    }
-
-    count
+// The same text with CRLF line endings, without its final newline, and with
+// whitespace trailing every line and the file
+#[cfg(test)]
+pub fn line_ending_variants(text: &str) -> [std::string::String; 3] {
+    [
+        text.replace('\n', "\r\n"),
+        text.trim_end_matches('\n').into(),
+        text.replace('\n', " \t\n") + "\n  \n",
+    ]
 }
-
-pub const fn count_line_len(text: &str) -> usize {
-    let len = text.len();
-    if len == 0 {
-        return 0;
-    }
-
-    let bytes: &[u8] = text.as_bytes();
-    let mut count = 1;
-    while count < len {
-        let b = bytes[count];
-        if b == b'\n' {
-            break;
-        }
-        count += 1;
-    }
-
-    count
-}
\ No newline at end of file
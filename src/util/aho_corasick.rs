@@ -0,0 +1,262 @@
+use heapless::Vec;
+
+// Distinct bytes the patterns may use between them, class 0 stands for any
+// byte that appears in no pattern
+const ALPHABET_LEN: usize = 32;
+const NONE: u16 = u16::MAX;
+const ROOT: u16 = 0;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BuildError {
+    EmptyPattern,
+    TooManyPatterns,
+    TooManyStates,
+    AlphabetTooLarge,
+}
+
+// A pattern found in the haystack, `end` is one past its last byte
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    pub pattern: usize,
+    pub end: usize,
+}
+
+// Multi-pattern matcher with up to S states and P patterns, made by a
+// `Builder`. Every transition is filled in, so matching takes one table
+// lookup per byte.
+pub struct AhoCorasick<const S: usize, const P: usize> {
+    classes: [u8; 256],
+    alphabet_len: usize,
+    next: Vec<[u16; ALPHABET_LEN], S>,
+    fail: Vec<u16, S>,
+    // First pattern ending exactly at each state
+    output: Vec<u16, S>,
+    // Nearest state along the fail chain that has an output
+    dict_link: Vec<u16, S>,
+    // Length of each pattern and the next pattern ending at the same state
+    patterns: Vec<(usize, u16), P>,
+}
+
+// Patterns are inserted into a trie one at a time, then `build` turns it into
+// the automaton
+pub struct Builder<const S: usize, const P: usize> {
+    trie: AhoCorasick<S, P>,
+}
+
+impl<const S: usize, const P: usize> Builder<S, P> {
+    pub fn new() -> Self {
+        assert!(S > 0 && S < NONE as usize && P < NONE as usize);
+        let mut trie = AhoCorasick {
+            classes: [0; 256],
+            alphabet_len: 1,
+            next: Vec::new(),
+            fail: Vec::new(),
+            output: Vec::new(),
+            dict_link: Vec::new(),
+            patterns: Vec::new()
+        };
+        let _ = trie.add_state();
+        Builder { trie }
+    }
+
+    // Adds a pattern to the trie, returning its index
+    pub fn insert<I: IntoIterator<Item = u8>>(&mut self, pattern: I) -> Result<usize, BuildError> {
+        self.trie.insert(pattern)
+    }
+
+    pub fn build(mut self) -> AhoCorasick<S, P> {
+        self.trie.build();
+        self.trie
+    }
+}
+
+impl<const S: usize, const P: usize> Default for Builder<S, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const S: usize, const P: usize> AhoCorasick<S, P> {
+    fn add_state(&mut self) -> Result<u16, BuildError> {
+        let id = self.next.len() as u16;
+        self.next.push([NONE; ALPHABET_LEN]).map_err(|_| BuildError::TooManyStates)?;
+        let _ = self.fail.push(ROOT);
+        let _ = self.output.push(NONE);
+        let _ = self.dict_link.push(NONE);
+        Ok(id)
+    }
+
+    fn class(&mut self, byte: u8) -> Result<usize, BuildError> {
+        if self.classes[byte as usize] == 0 {
+            if self.alphabet_len == ALPHABET_LEN {
+                return Err(BuildError::AlphabetTooLarge);
+            }
+            self.classes[byte as usize] = self.alphabet_len as u8;
+            self.alphabet_len += 1;
+        }
+        Ok(self.classes[byte as usize] as usize)
+    }
+
+    fn insert<I: IntoIterator<Item = u8>>(&mut self, pattern: I) -> Result<usize, BuildError> {
+        if self.patterns.is_full() {
+            return Err(BuildError::TooManyPatterns);
+        }
+
+        let mut state = ROOT;
+        let mut len = 0;
+        for byte in pattern {
+            let class = self.class(byte)?;
+            state = match self.next[state as usize][class] {
+                NONE => {
+                    let child = self.add_state()?;
+                    self.next[state as usize][class] = child;
+                    child
+                }
+                child => child
+            };
+            len += 1;
+        }
+        if len == 0 {
+            return Err(BuildError::EmptyPattern);
+        }
+
+        let id = self.patterns.len();
+        let _ = self.patterns.push((len, self.output[state as usize]));
+        self.output[state as usize] = id as u16;
+        Ok(id)
+    }
+
+    // Works out the fail links breadth first, filling in every missing
+    // transition from the state's fail link as it goes
+    fn build(&mut self) {
+        let mut queue: Vec<u16, S> = Vec::new();
+        for class in 0..self.alphabet_len {
+            match self.next[ROOT as usize][class] {
+                NONE => self.next[ROOT as usize][class] = ROOT,
+                child => {
+                    let _ = queue.push(child);
+                }
+            }
+        }
+
+        let mut head = 0;
+        while head < queue.len() {
+            let state = queue[head] as usize;
+            head += 1;
+            let fail = self.fail[state] as usize;
+            for class in 0..self.alphabet_len {
+                match self.next[state][class] {
+                    NONE => self.next[state][class] = self.next[fail][class],
+                    child => {
+                        let child_fail = self.next[fail][class];
+                        self.fail[child as usize] = child_fail;
+                        self.dict_link[child as usize] = match self.output[child_fail as usize] {
+                            NONE => self.dict_link[child_fail as usize],
+                            _ => child_fail
+                        };
+                        let _ = queue.push(child);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn pattern_len(&self, pattern: usize) -> usize {
+        self.patterns[pattern].0
+    }
+
+    pub fn find_iter<I: Iterator<Item = u8>>(&self, haystack: I) -> Matches<'_, I, S, P> {
+        Matches {
+            automaton: self,
+            haystack,
+            state: ROOT,
+            pos: 0,
+            out_state: NONE,
+            pattern: NONE
+        }
+    }
+}
+
+pub struct Matches<'a, I, const S: usize, const P: usize> {
+    automaton: &'a AhoCorasick<S, P>,
+    haystack: I,
+    state: u16,
+    pos: usize,
+    // Walks the dictionary links, then each state's list of patterns
+    out_state: u16,
+    pattern: u16,
+}
+
+impl<I: Iterator<Item = u8>, const S: usize, const P: usize> Iterator for Matches<'_, I, S, P> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ac = self.automaton;
+        loop {
+            if self.pattern != NONE {
+                let pattern = self.pattern as usize;
+                self.pattern = ac.patterns[pattern].1;
+                return Some(Match { pattern, end: self.pos });
+            }
+            if self.out_state != NONE {
+                let state = self.out_state as usize;
+                self.pattern = ac.output[state];
+                self.out_state = ac.dict_link[state];
+                continue;
+            }
+
+            let byte = self.haystack.next()?;
+            self.state = ac.next[self.state as usize][ac.classes[byte as usize] as usize];
+            self.pos += 1;
+            self.out_state = self.state;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn automaton(patterns: &[&str]) -> AhoCorasick<32, 8> {
+        let mut builder = Builder::new();
+        for p in patterns {
+            builder.insert(p.bytes()).unwrap();
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn test_find_iter() {
+        let ac = automaton(&["he", "she", "his", "hers"]);
+        let found: std::vec::Vec<Match> = ac.find_iter("ushers".bytes()).collect();
+        assert_eq!(found, [
+            Match { pattern: 1, end: 4 },
+            Match { pattern: 0, end: 4 },
+            Match { pattern: 3, end: 6 },
+        ]);
+        assert_eq!(ac.pattern_len(3), 4);
+    }
+
+    #[test]
+    fn test_overlapping_and_duplicates() {
+        let ac = automaton(&["aa", "a", "aa"]);
+        let found: std::vec::Vec<(usize, usize)> = ac.find_iter("xaaa".bytes())
+            .map(|m| (m.pattern, m.end))
+            .collect();
+        assert_eq!(found, [(1, 2), (2, 3), (0, 3), (1, 3), (2, 4), (0, 4), (1, 4)]);
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let mut ac: Builder<4, 1> = Builder::new();
+        assert_eq!(ac.insert("".bytes()), Err(BuildError::EmptyPattern));
+        assert_eq!(ac.insert("abcd".bytes()), Err(BuildError::TooManyStates));
+
+        let mut ac: Builder<4, 1> = Builder::new();
+        assert_eq!(ac.insert("ab".bytes()), Ok(0));
+        assert_eq!(ac.insert("a".bytes()), Err(BuildError::TooManyPatterns));
+
+        let mut ac: Builder<64, 1> = Builder::new();
+        assert_eq!(ac.insert(0..40), Err(BuildError::AlphabetTooLarge));
+    }
+}
@@ -0,0 +1,228 @@
+use heapless::Vec;
+
+// Every sequence of `len` symbols drawn from `alphabet`. Works like an
+// odometer with the first position turning fastest.
+pub struct CartesianPower<'a, T, const N: usize> {
+    alphabet: &'a [T],
+    digits: Vec<usize, N>,
+    done: bool,
+}
+
+impl<'a, T: Copy, const N: usize> CartesianPower<'a, T, N> {
+    pub fn new(alphabet: &'a [T], len: usize) -> Self {
+        assert!(len <= N, "sequence length exceeds capacity");
+        let mut digits = Vec::new();
+        let _ = digits.resize(len, 0);
+        CartesianPower {
+            alphabet,
+            digits,
+            done: alphabet.is_empty() && len > 0
+        }
+    }
+}
+
+impl<T: Copy, const N: usize> Iterator for CartesianPower<'_, T, N> {
+    type Item = Vec<T, N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let item = self.digits.iter().map(|&d| self.alphabet[d]).collect();
+
+        // Bump the odometer, we are finished once every digit has rolled over
+        self.done = true;
+        for d in self.digits.iter_mut() {
+            *d += 1;
+            if *d < self.alphabet.len() {
+                self.done = false;
+                break;
+            }
+            *d = 0;
+        }
+
+        Some(item)
+    }
+}
+
+// Every way of picking `k` items from `items` with the original order kept,
+// yielded in lexicographic order of the picked indices.
+pub struct Combinations<'a, T, const K: usize> {
+    items: &'a [T],
+    indices: Vec<usize, K>,
+    done: bool,
+}
+
+impl<'a, T: Copy, const K: usize> Combinations<'a, T, K> {
+    pub fn new(items: &'a [T], k: usize) -> Self {
+        assert!(k <= K, "combination length exceeds capacity");
+        Combinations {
+            items,
+            indices: (0..k).collect(),
+            done: k > items.len()
+        }
+    }
+}
+
+impl<T: Copy, const K: usize> Iterator for Combinations<'_, T, K> {
+    type Item = Vec<T, K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let item = self.indices.iter().map(|&i| self.items[i]).collect();
+
+        // Find the rightmost index that can still move right
+        let n = self.items.len();
+        let k = self.indices.len();
+        match (0..k).rev().find(|&i| self.indices[i] != i + n - k) {
+            Some(i) => {
+                self.indices[i] += 1;
+                for j in (i + 1)..k {
+                    self.indices[j] = self.indices[j - 1] + 1;
+                }
+            }
+            None => self.done = true
+        }
+
+        Some(item)
+    }
+}
+
+// Every ordering of `items`, generated with Heap's algorithm so each
+// permutation differs from the previous one by a single swap.
+pub struct Permutations<T, const N: usize> {
+    items: Vec<T, N>,
+    counters: [usize; N],
+    i: usize,
+    first: bool,
+}
+
+impl<T: Clone, const N: usize> Permutations<T, N> {
+    pub fn new(items: &[T]) -> Self {
+        assert!(items.len() <= N, "too many items for capacity");
+        Permutations {
+            items: Vec::from_slice(items).unwrap(),
+            counters: [0; N],
+            i: 1,
+            first: true
+        }
+    }
+}
+
+impl<T: Clone, const N: usize> Iterator for Permutations<T, N> {
+    type Item = Vec<T, N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.first {
+            self.first = false;
+            return Some(self.items.clone());
+        }
+
+        while self.i < self.items.len() {
+            let i = self.i;
+            if self.counters[i] < i {
+                if i.is_multiple_of(2) {
+                    self.items.swap(0, i);
+                } else {
+                    self.items.swap(self.counters[i], i);
+                }
+                self.counters[i] += 1;
+                self.i = 1;
+                return Some(self.items.clone());
+            } else {
+                self.counters[i] = 0;
+                self.i += 1;
+            }
+        }
+
+        None
+    }
+}
+
+// Every subset of an `n` element set as a bitmask, counting up from the
+// empty set. Bit `i` set means item `i` is in the subset.
+pub struct Subsets {
+    next: Option<u64>,
+    last: u64,
+}
+
+impl Subsets {
+    pub fn new(n: u32) -> Self {
+        assert!(n <= u64::BITS, "too many items for a u64 mask");
+        Subsets {
+            next: Some(0),
+            last: if n == 0 { 0 } else { u64::MAX >> (u64::BITS - n) }
+        }
+    }
+}
+
+impl Iterator for Subsets {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mask = self.next?;
+        self.next = if mask == self.last { None } else { Some(mask + 1) };
+        Some(mask)
+    }
+}
+
+// Pick the items of a subset yielded by `Subsets`, None if more than N are
+// picked
+pub fn select<T: Copy, const N: usize>(items: &[T], mask: u64) -> Option<Vec<T, N>> {
+    let mut picked = Vec::new();
+    for (i, &x) in items.iter().enumerate().take(u64::BITS as usize) {
+        if mask & (1 << i) != 0 {
+            picked.push(x).ok()?;
+        }
+    }
+    Some(picked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cartesian_power() {
+        let all: std::vec::Vec<Vec<char, 4>> = CartesianPower::new(&['a', 'b', 'c'], 2).collect();
+        assert_eq!(all.len(), 9);
+        assert_eq!(all[0], ['a', 'a']);
+        assert_eq!(all[1], ['b', 'a']);
+        assert_eq!(all[8], ['c', 'c']);
+        assert_eq!(CartesianPower::<u8, 4>::new(&[0, 1], 0).count(), 1);
+        assert_eq!(CartesianPower::<u8, 4>::new(&[], 3).count(), 0);
+    }
+
+    #[test]
+    fn test_combinations() {
+        let all: std::vec::Vec<Vec<u8, 3>> = Combinations::new(&[1, 2, 3, 4], 2).collect();
+        let expected: [[u8; 2]; 6] = [[1, 2], [1, 3], [1, 4], [2, 3], [2, 4], [3, 4]];
+        assert_eq!(all, expected);
+        assert_eq!(Combinations::<u8, 3>::new(&[1, 2], 3).count(), 0);
+        assert_eq!(Combinations::<u8, 3>::new(&[1, 2], 0).count(), 1);
+    }
+
+    #[test]
+    fn test_permutations() {
+        let mut all: std::vec::Vec<Vec<u8, 4>> = Permutations::new(&[1, 2, 3, 4]).collect();
+        assert_eq!(all.len(), 24);
+        assert_eq!(all[0], [1, 2, 3, 4]);
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), 24);
+        assert_eq!(Permutations::<u8, 4>::new(&[]).count(), 1);
+    }
+
+    #[test]
+    fn test_subsets() {
+        let all: std::vec::Vec<u64> = Subsets::new(3).collect();
+        assert_eq!(all, [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(Subsets::new(0).count(), 1);
+        assert_eq!(Subsets::new(64).nth(5), Some(5));
+        let picked: Vec<char, 3> = select(&['a', 'b', 'c'], 0b101).unwrap();
+        assert_eq!(picked, ['a', 'c']);
+        assert_eq!(select::<char, 1>(&['a', 'b', 'c'], 0b101), None);
+    }
+}
@@ -0,0 +1,188 @@
+// Measuring grids and line based puzzle text. Uses nothing outside `core`,
+// so build.rs includes this file too and checks grids the same way.
+
+// Lines with any text in them, ignoring whitespace after the last one.
+// Works for LF and CRLF files, with or without a final newline.
+pub const fn count_lines(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let end = trim_end(bytes);
+    if end == 0 {
+        return 0;
+    }
+
+    let mut count = 1;
+    let mut i = 0;
+    while i < end {
+        if bytes[i] == b'\n' {
+            count += 1;
+        }
+        i += 1;
+    }
+
+    count
+}
+
+// Length of the first line, not counting its line ending or any whitespace
+// trailing it
+pub const fn count_line_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut len = 0;
+    let mut i = 0;
+    while i < bytes.len() && bytes[i] != b'\n' {
+        i += 1;
+        if !bytes[i - 1].is_ascii_whitespace() {
+            len = i;
+        }
+    }
+
+    len
+}
+
+// Distance from the start of one line to the next, so the first line's
+// length plus its line ending
+pub const fn count_line_stride(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() && bytes[i] != b'\n' {
+        i += 1;
+    }
+
+    if i < bytes.len() {
+        i + 1
+    } else {
+        i
+    }
+}
+
+// A row of a grid that does not start where the first row says it should,
+// because its width or the whitespace after it differs. `line` counts from 1.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MisalignedRow {
+    pub line: usize,
+}
+
+// Width, height and stride of a grid laid out like the puzzle text, so that
+// row y starts at y * stride. Every row must be as wide as the first and,
+// apart from the last, end in the same line ending and padding.
+pub const fn grid_shape(text: &str) -> Result<(usize, usize, usize), MisalignedRow> {
+    let bytes = text.as_bytes();
+    let shape = (count_line_len(text), count_lines(text), count_line_stride(text));
+    let (width, height, stride) = shape;
+    let mut y = 0;
+    while y < height {
+        let start = y * stride;
+        let mut end = start;
+        while end < bytes.len() && bytes[end] != b'\n' {
+            end += 1;
+        }
+        let row = bytes.split_at(end).0.split_at(start).1;
+        if trim_end(row) != width || (y + 1 < height && end + 1 != start + stride) {
+            return Err(MisalignedRow { line: y + 1 });
+        }
+        y += 1;
+    }
+
+    Ok(shape)
+}
+
+// A grid borrowed from the text it was read from, so no cell is copied.
+// Row y starts at y * stride, which leaves room for each row's line ending.
+#[derive(Copy, Clone, Debug)]
+pub struct Grid<'a> {
+    cells: &'a [u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+}
+
+impl<'a> Grid<'a> {
+    pub fn parse(text: &'a str) -> Result<Self, MisalignedRow> {
+        let (width, height, stride) = grid_shape(text)?;
+        Ok(Grid { cells: text.as_bytes(), width, height, stride })
+    }
+
+    // Rows of `width` cells packed with no separator between them. Cells
+    // after the last full row are left out.
+    pub fn from_cells(cells: &'a [u8], width: usize) -> Self {
+        let height = cells.len().checked_div(width).unwrap_or(0);
+        Grid { cells, width, height, stride: width }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<u8> {
+        if x >= self.width || y >= self.height {
+            None
+        } else {
+            self.cells.get(y * self.stride + x).copied()
+        }
+    }
+
+    // Position of the first cell holding `c`, reading row by row
+    pub fn find(&self, c: u8) -> Option<(usize, usize)> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .find(|&(x, y)| self.get(x, y) == Some(c))
+    }
+}
+
+// Index just past the last byte that is not whitespace
+const fn trim_end(bytes: &[u8]) -> usize {
+    let mut end = bytes.len();
+    while end > 0 && bytes[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+    end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_counts() {
+        for text in ["ab\ncd\nef\n", "ab\r\ncd\r\nef\r\n", "ab\ncd\nef", "ab  \ncd\nef\n\n \n"] {
+            assert_eq!((count_lines(text), count_line_len(text)), (3, 2), "{:?}", text);
+        }
+        assert_eq!(count_line_stride("ab\ncd"), 3);
+        assert_eq!(count_line_stride("ab\r\ncd"), 4);
+        assert_eq!(count_line_stride("ab"), 2);
+        assert_eq!(count_lines(""), 0);
+        assert_eq!(count_lines("\r\n"), 0);
+        assert_eq!(count_line_len(""), 0);
+    }
+
+    #[test]
+    fn test_grid_shape() {
+        assert_eq!(grid_shape("ab\ncd\nef\n"), Ok((2, 3, 3)));
+        assert_eq!(grid_shape("ab \r\ncd \r\nef"), Ok((2, 3, 5)));
+        assert_eq!(grid_shape("ab\ncd\nef  \n\n"), Ok((2, 3, 3)));
+        assert_eq!(grid_shape(""), Ok((0, 0, 0)));
+        assert_eq!(grid_shape("ab \ncd\nef\n"), Err(MisalignedRow { line: 2 }));
+        assert_eq!(grid_shape("ab\ncd \nef\n"), Err(MisalignedRow { line: 2 }));
+        assert_eq!(grid_shape("ab\nc\nef\n"), Err(MisalignedRow { line: 2 }));
+        assert_eq!(grid_shape("ab\ncd\nefg\n"), Err(MisalignedRow { line: 3 }));
+        assert_eq!(grid_shape("ab\n\ncd\n"), Err(MisalignedRow { line: 2 }));
+    }
+
+    #[test]
+    fn test_grid() {
+        let grid = Grid::parse("ab \r\ncd \r\nef").unwrap();
+        assert_eq!((grid.width(), grid.height()), (2, 3));
+        assert_eq!((grid.get(1, 1), grid.get(2, 1), grid.get(0, 3)), (Some(b'd'), None, None));
+        assert_eq!((grid.find(b'e'), grid.find(b' '), grid.find(b'\n')), (Some((0, 2)), None, None));
+
+        let grid = Grid::from_cells(b"abcdefg", 3);
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!((grid.get(0, 1), grid.find(b'f'), grid.find(b'g')), (Some(b'd'), Some((2, 1)), None));
+
+        let grid = Grid::from_cells(b"abc", 0);
+        assert_eq!((grid.height(), grid.get(0, 0), grid.find(b'a')), (0, None, None));
+    }
+}
@@ -0,0 +1,134 @@
+use heapless::Vec;
+use nom::character::complete::{line_ending, space0};
+use nom::error::{ErrorKind, ParseError};
+use nom::{AsChar, Compare, Err, IResult, InputIter, InputLength, InputTakeAtPosition, Parser, Slice};
+use core::ops::{Range, RangeFrom, RangeTo};
+
+// nom combinators that collect into a fixed capacity `heapless::Vec`. Going
+// over capacity fails with `ErrorKind::TooLarge` rather than dropping items,
+// and fails hard so an enclosing `alt` or repeat does not quietly stop there.
+
+fn push<I, O, E: ParseError<I>, const N: usize>(list: &mut Vec<O, N>, item: O, input: I) -> Result<(), Err<E>> {
+    list.push(item).map_err(|_| Err::Failure(E::from_error_kind(input, ErrorKind::TooLarge)))
+}
+
+// Like nom's `separated_list1`: one or more `f`, with `sep` between them
+pub fn separated_list1_heapless<const N: usize, I, O, O2, E, F, G>(
+    mut sep: G,
+    mut f: F,
+) -> impl FnMut(I) -> IResult<I, Vec<O, N>, E>
+where
+    I: Clone + InputLength,
+    F: Parser<I, O, E>,
+    G: Parser<I, O2, E>,
+    E: ParseError<I>,
+{
+    move |input: I| {
+        let mut list = Vec::new();
+        let (mut input, first) = f.parse(input)?;
+        push(&mut list, first, input.clone())?;
+
+        loop {
+            let len = input.input_len();
+            let rest = match sep.parse(input.clone()) {
+                Err(Err::Error(_)) => return Ok((input, list)),
+                Err(e) => return Err(e),
+                Ok((rest, _)) => rest
+            };
+            // A separator that matches nothing would loop forever
+            if rest.input_len() == len {
+                return Err(Err::Error(E::from_error_kind(rest, ErrorKind::SeparatedList)));
+            }
+            match f.parse(rest) {
+                Err(Err::Error(_)) => return Ok((input, list)),
+                Err(e) => return Err(e),
+                Ok((rest, item)) => {
+                    push(&mut list, item, input)?;
+                    input = rest;
+                }
+            }
+        }
+    }
+}
+
+// The end of a line, LF or CRLF, along with any spaces or tabs before it
+pub fn line_end<I, E>(input: I) -> IResult<I, I, E>
+where
+    I: Clone + InputLength + InputIter + InputTakeAtPosition + Compare<&'static str>,
+    I: Slice<Range<usize>> + Slice<RangeFrom<usize>> + Slice<RangeTo<usize>>,
+    <I as InputTakeAtPosition>::Item: AsChar + Clone,
+    E: ParseError<I>,
+{
+    let (input, _) = space0(input)?;
+    line_ending(input)
+}
+
+// Zero or more `f`, one per line. The last line may end without a newline.
+pub fn lines_heapless<const N: usize, I, O, E, F>(mut f: F) -> impl FnMut(I) -> IResult<I, Vec<O, N>, E>
+where
+    I: Clone + InputLength + InputIter + InputTakeAtPosition + Compare<&'static str>,
+    I: Slice<Range<usize>> + Slice<RangeFrom<usize>> + Slice<RangeTo<usize>>,
+    <I as InputTakeAtPosition>::Item: AsChar + Clone,
+    F: Parser<I, O, E>,
+    E: ParseError<I>,
+{
+    move |mut input: I| {
+        let mut list = Vec::new();
+        loop {
+            let (rest, item) = match f.parse(input.clone()) {
+                Err(Err::Error(_)) => return Ok((input, list)),
+                Err(e) => return Err(e),
+                Ok(parsed) => parsed
+            };
+            let rest = match line_end::<I, E>(rest.clone()) {
+                Ok((rest, _)) => rest,
+                Err(Err::Error(_)) => match space0::<I, E>(rest)? {
+                    (rest, _) if rest.input_len() == 0 => {
+                        push(&mut list, item, input)?;
+                        return Ok((rest, list));
+                    }
+                    _ => return Ok((input, list))
+                },
+                Err(e) => return Err(e)
+            };
+            push(&mut list, item, input)?;
+            input = rest;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::bytes::complete::tag;
+    use nom::error::Error;
+    use crate::util::scan::number;
+
+    #[test]
+    fn test_separated_list1() {
+        let mut parser = separated_list1_heapless::<3, _, u8, _, Error<&str>, _, _>(tag(","), number);
+        assert_eq!(parser("1,2,3\n"), Ok(("\n", Vec::from_slice(&[1, 2, 3]).unwrap())));
+        assert_eq!(parser("1,2,"), Ok((",", Vec::from_slice(&[1, 2]).unwrap())));
+        assert!(matches!(parser(""), Err(Err::Error(_))));
+        assert_eq!(parser("1,2,3,4"), Err(Err::Failure(Error::new(",4", ErrorKind::TooLarge))));
+    }
+
+    #[test]
+    fn test_line_end() {
+        assert_eq!(line_end::<_, Error<&str>>(" \r\nx"), Ok(("x", "\r\n")));
+        assert_eq!(line_end::<_, Error<&str>>("\nx"), Ok(("x", "\n")));
+        assert!(line_end::<_, Error<&str>>("x\n").is_err());
+    }
+
+    #[test]
+    fn test_lines() {
+        let mut parser = lines_heapless::<2, _, u8, Error<&str>, _>(number);
+        for text in ["1\n2\n", "1\r\n2\r\n", "1\n2", "1 \t\n2  "] {
+            assert_eq!(parser(text), Ok(("", Vec::from_slice(&[1, 2]).unwrap())), "{:?}", text);
+        }
+        assert_eq!(parser("1\n2\n\n3\n"), Ok(("\n3\n", Vec::from_slice(&[1, 2]).unwrap())));
+        assert_eq!(parser("1\n2x"), Ok(("2x", Vec::from_slice(&[1]).unwrap())));
+        assert_eq!(parser("\n"), Ok(("\n", Vec::new())));
+        assert_eq!(parser("1\n2\n3\n"), Err(Err::Failure(Error::new("3\n", ErrorKind::TooLarge))));
+    }
+}
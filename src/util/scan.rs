@@ -0,0 +1,212 @@
+use core::marker::PhantomData;
+use nom::error::{Error, ErrorKind};
+use nom::IResult;
+
+// Integer parsing straight off the input bytes, without going through
+// `str::parse`. The index based functions are `const` so the const solvers
+// can share them.
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ScanError {
+    NoDigits,
+    Overflow,
+    // A '-' in front of a number read as an unsigned type
+    Negative,
+}
+
+// Reads the run of ASCII digits starting at `at`, giving the number and the
+// index just past it. None if there are no digits or the number overflows.
+pub const fn digits(bytes: &[u8], at: usize) -> Option<(u64, usize)> {
+    let mut i = at;
+    let mut n: u64 = 0;
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        let Some(shifted) = n.checked_mul(10) else {
+            return None;
+        };
+        let Some(sum) = shifted.checked_add((bytes[i] - b'0') as u64) else {
+            return None;
+        };
+        n = sum;
+        i += 1;
+    }
+
+    if i == at {
+        None
+    } else {
+        Some((n, i))
+    }
+}
+
+// Index of the first byte from `at` that is not a space, tab, or the \r of a
+// CRLF line ending
+pub const fn skip_blanks(bytes: &[u8], at: usize) -> usize {
+    let mut i = at;
+    while i < bytes.len() && (bytes[i] == b' ' || bytes[i] == b'\t' || bytes[i] == b'\r') {
+        i += 1;
+    }
+    i
+}
+
+// Index of the first byte from `at` that is not ASCII whitespace
+pub const fn skip_whitespace(bytes: &[u8], at: usize) -> usize {
+    let mut i = at;
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+// The bytes left once any leading `separators` are dropped
+pub fn skip_separators<'a>(bytes: &'a [u8], separators: &[u8]) -> &'a [u8] {
+    let n = bytes.iter().take_while(|b| separators.contains(b)).count();
+    &bytes[n..]
+}
+
+pub trait Integer: Copy {
+    const SIGNED: bool;
+
+    // None if the magnitude does not fit. Never called with `negative` set
+    // when the type is unsigned.
+    fn from_magnitude(n: u64, negative: bool) -> Option<Self>;
+}
+
+macro_rules! impl_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const SIGNED: bool = false;
+
+                fn from_magnitude(n: u64, _negative: bool) -> Option<Self> {
+                    Self::try_from(n).ok()
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_signed {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const SIGNED: bool = true;
+
+                fn from_magnitude(n: u64, negative: bool) -> Option<Self> {
+                    let n = n as i128;
+                    Self::try_from(if negative { -n } else { n }).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned!(u8, u16, u32, u64, usize);
+impl_signed!(i8, i16, i32, i64, isize);
+
+// Reads an integer from the start of `bytes`, with a leading '-' if T is
+// signed, and returns it with the bytes after it
+pub fn integer<T: Integer>(bytes: &[u8]) -> Result<(T, &[u8]), ScanError> {
+    let negative = bytes.first() == Some(&b'-');
+    let start = negative as usize;
+    if bytes.get(start).is_none_or(|b| !b.is_ascii_digit()) {
+        return Err(ScanError::NoDigits);
+    }
+    if negative && !T::SIGNED {
+        return Err(ScanError::Negative);
+    }
+    let (n, end) = digits(bytes, start).ok_or(ScanError::Overflow)?;
+    let n = T::from_magnitude(n, negative).ok_or(ScanError::Overflow)?;
+    Ok((n, &bytes[end..]))
+}
+
+// nom parser over `integer`, failing with `ErrorKind::Digit` when there is
+// no number T can hold and `ErrorKind::TooLarge` when it does not fit T
+pub fn number<T: Integer>(input: &str) -> IResult<&str, T> {
+    match integer(input.as_bytes()) {
+        Ok((n, rest)) => Ok((&input[input.len() - rest.len()..], n)),
+        Err(ScanError::NoDigits | ScanError::Negative) => {
+            Err(nom::Err::Error(Error::new(input, ErrorKind::Digit)))
+        }
+        Err(ScanError::Overflow) => Err(nom::Err::Error(Error::new(input, ErrorKind::TooLarge)))
+    }
+}
+
+// Every number in a line, whatever separates them. A '-' just before the
+// digits makes the number negative, or is a `ScanError::Negative` if T is
+// unsigned.
+pub struct Numbers<'a, T> {
+    bytes: &'a [u8],
+    integer: PhantomData<T>,
+}
+
+pub fn numbers<T: Integer>(bytes: &[u8]) -> Numbers<'_, T> {
+    Numbers { bytes, integer: PhantomData }
+}
+
+impl<T: Integer> Iterator for Numbers<'_, T> {
+    type Item = Result<T, ScanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.bytes.iter().enumerate().position(|(i, b)| {
+            b.is_ascii_digit() || (*b == b'-' && self.bytes.get(i + 1).is_some_and(u8::is_ascii_digit))
+        })?;
+        match integer(&self.bytes[start..]) {
+            Ok((n, rest)) => {
+                self.bytes = rest;
+                Some(Ok(n))
+            }
+            Err(e) => {
+                // Step over the sign and digits that failed so the next call
+                // moves on
+                let rest = &self.bytes[start + 1..];
+                self.bytes = skip_separators(rest, b"0123456789");
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer() {
+        assert_eq!(integer::<u8>(b"255,"), Ok((255, &b","[..])));
+        assert_eq!(integer::<u8>(b"256"), Err(ScanError::Overflow));
+        assert_eq!(integer::<u8>(b"-1"), Err(ScanError::Negative));
+        assert_eq!(integer::<u8>(b"-x"), Err(ScanError::NoDigits));
+        assert_eq!(integer::<i16>(b"-32768 "), Ok((-32768, &b" "[..])));
+        assert_eq!(integer::<i16>(b"32768"), Err(ScanError::Overflow));
+        assert_eq!(integer::<i64>(b"-"), Err(ScanError::NoDigits));
+        assert_eq!(integer::<u64>(b"18446744073709551615"), Ok((u64::MAX, &b""[..])));
+        assert_eq!(integer::<u64>(b"18446744073709551616"), Err(ScanError::Overflow));
+        assert_eq!(integer::<u32>(b"x1"), Err(ScanError::NoDigits));
+    }
+
+    #[test]
+    fn test_skip() {
+        assert_eq!(skip_blanks(b" \t 1\n", 0), 3);
+        assert_eq!(skip_blanks(b"1 \r\n", 1), 3);
+        assert_eq!(skip_whitespace(b" \r\n 1", 0), 4);
+        assert_eq!(skip_separators(b", ,7", b", "), b"7");
+        assert_eq!(digits(b"12ab", 0), Some((12, 2)));
+        assert_eq!(digits(b"ab", 0), None);
+    }
+
+    #[test]
+    fn test_number() {
+        assert_eq!(number::<u64>("190: 10 19"), Ok((": 10 19", 190)));
+        assert!(number::<u8>("300|1").is_err());
+        assert!(number::<u8>("|1").is_err());
+        assert!(number::<u32>("-1").is_err());
+    }
+
+    #[test]
+    fn test_numbers() {
+        let found: std::vec::Vec<_> = numbers::<i32>(b"7 -6 4-2  1").collect();
+        assert_eq!(found, [Ok(7), Ok(-6), Ok(4), Ok(-2), Ok(1)]);
+        let found: std::vec::Vec<_> = numbers::<u8>(b"mul(2,4) 999 x-3 5").collect();
+        assert_eq!(found, [Ok(2), Ok(4), Err(ScanError::Overflow), Err(ScanError::Negative), Ok(5)]);
+        assert_eq!(numbers::<u8>(b"  ").next(), None);
+    }
+}